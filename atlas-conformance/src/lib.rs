@@ -0,0 +1,242 @@
+//! atlas-conformance
+//!
+//! Golden wire vectors for the P2P protocol.
+//!
+//! Every message a node puts on the wire (and every byte string a node
+//! signs) is encoded from a fixed sample and compared byte for byte against
+//! the hex files in `vectors/`. A change to a message type that would make
+//! old and new nodes disagree fails here instead of splitting a testnet.
+//!
+//! If a wire change is intended, bump `version::PROTOCOL_VERSION` and
+//! regenerate the vectors with `ATLAS_UPDATE_GOLDEN=1 cargo test -p atlas-conformance`.
+//!
+//! `vectors/signing.json` additionally lists, for every signed message kind,
+//! a fully signed sample, its signing bytes and the signature made with a
+//! fixed test key, for wallets and other implementations to check against.
+
+use atlas_db::{env::storage::StateRoot, network::p2p::{envelope, protocol::{Heartbeat, TxBundle, TxRequest}}};
+use atlas_sdk::{
+    auth::{admin::admin_signing_bytes, ed25519::Ed25519Authenticator, Authenticator},
+    env::{
+        consensus::{certificate::QuorumCertificate, evidence::VoteEquivocation, types::Vote},
+        misbehavior::{misbehavior_signing_bytes, MisbehaviorReport},
+        proposal::{signing_bytes, Proposal},
+        signing_domains,
+        view_change::{view_change_signing_bytes, ViewChange},
+        vote_data::{vote_signing_bytes, VoteData},
+    },
+    utils::NodeId,
+};
+
+/// Directory holding the golden `<name>.hex` files.
+pub const VECTORS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/vectors");
+
+fn node(id: &str) -> NodeId {
+    NodeId(id.to_string())
+}
+
+/// Fixed proposal used by the vectors.
+pub fn sample_proposal() -> Proposal {
+    Proposal {
+        id: "prop-1".into(),
+        proposer: node("node-a"),
+        content: r#"{"action":"add_edge","from":"A","to":"B"}"#.into(),
+        parent: Some("prop-0".into()),
+        signature: [0x11; 64],
+        public_key: vec![0x22; 32],
+        request_id: Some("req-1".into()),
+    }
+}
+
+/// Fixed vote used by the vectors.
+pub fn sample_vote(vote: Vote) -> VoteData {
+    VoteData {
+        proposal_id: "prop-1".into(),
+        vote,
+        voter: node("node-b"),
+        signature: [0x33; 64],
+        public_key: vec![0x44; 32],
+    }
+}
+
+/// Fixed view change used by the vectors.
+pub fn sample_view_change() -> ViewChange {
+    ViewChange {
+        new_view: 7,
+        voter: node("node-c"),
+        signature: [0x55; 64],
+        public_key: vec![0x66; 32],
+    }
+}
+
+/// Fixed misbehavior report used by the vectors.
+pub fn sample_report() -> MisbehaviorReport {
+    MisbehaviorReport {
+        reporter: node("node-a"),
+        evidence: VoteEquivocation { first: sample_vote(Vote::Yes), second: sample_vote(Vote::No) },
+        signature: [0x77; 64],
+        public_key: vec![0x22; 32],
+    }
+}
+
+fn encode<T: serde::Serialize>(value: &T) -> Vec<u8> {
+    bincode::serialize(value).expect("encode vector")
+}
+
+/// Seed of the key that signs the JSON signing vectors. Test material only.
+pub const SIGNING_SEED: [u8; 32] = [0x42; 32];
+
+/// Signed samples of every message kind in [`signing_domains::ALL`], as JSON.
+///
+/// Each entry holds the message (signed with the [`SIGNING_SEED`] key), the
+/// hex signing bytes and the hex signature.
+pub fn signing_vectors() -> serde_json::Value {
+    let auth = Ed25519Authenticator::from_bytes(&SIGNING_SEED).expect("test key");
+    let public_key = auth.public_key();
+    let sign = |bytes: &[u8]| -> [u8; 64] {
+        auth.sign(bytes.to_vec()).expect("sign").try_into().expect("64-byte signature")
+    };
+
+    let mut proposal = Proposal { public_key: public_key.clone(), ..sample_proposal() };
+    proposal.signature = sign(&signing_bytes(&proposal));
+
+    let mut vote = VoteData { public_key: public_key.clone(), ..sample_vote(Vote::Yes) };
+    vote.signature = sign(&vote_signing_bytes(&vote));
+
+    let mut view_change = ViewChange { public_key: public_key.clone(), ..sample_view_change() };
+    view_change.signature = sign(&view_change_signing_bytes(&view_change));
+
+    let mut report = MisbehaviorReport { public_key: public_key.clone(), ..sample_report() };
+    report.signature = sign(&misbehavior_signing_bytes(&report));
+
+    let (method, timestamp_ms, nonce, body_digest) = ("/atlas.ProposalService/SetFaults", 1_700_000_000_000, 42, [0xB0; 32]);
+    let admin_bytes = admin_signing_bytes(method, timestamp_ms, nonce, &body_digest);
+    let admin_signature = sign(&admin_bytes);
+
+    let entries = [
+        ("proposal", serde_json::to_value(&proposal), signing_bytes(&proposal), proposal.signature),
+        ("vote", serde_json::to_value(&vote), vote_signing_bytes(&vote), vote.signature),
+        ("view_change", serde_json::to_value(&view_change), view_change_signing_bytes(&view_change), view_change.signature),
+        ("misbehavior", serde_json::to_value(&report), misbehavior_signing_bytes(&report), report.signature),
+        ("admin", Ok(serde_json::json!({
+            "method": method,
+            "timestamp_ms": timestamp_ms,
+            "nonce": nonce,
+            "body_digest": hex::encode(body_digest),
+        })), admin_bytes, admin_signature),
+    ];
+
+    serde_json::json!({
+        "public_key": hex::encode(&public_key),
+        "vectors": entries.into_iter().map(|(kind, message, bytes, signature)| serde_json::json!({
+            "kind": kind,
+            "domain": signing_domains::ALL.iter().find(|(k, _)| *k == kind).and_then(|(_, d)| *d),
+            "message": message.expect("encode message"),
+            "signing_bytes": hex::encode(bytes),
+            "signature": hex::encode(signature),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// Every golden vector, by name.
+pub fn vectors() -> Vec<(&'static str, Vec<u8>)> {
+    let proposal = sample_proposal();
+    let vote = sample_vote(Vote::Yes);
+    let view_change = sample_view_change();
+    let report = sample_report();
+
+    vec![
+        ("proposal", encode(&proposal)),
+        ("proposal.sign", signing_bytes(&proposal)),
+        ("vote", encode(&vote)),
+        ("vote.sign", vote_signing_bytes(&vote)),
+        ("view_change", encode(&view_change)),
+        ("view_change.sign", view_change_signing_bytes(&view_change)),
+        ("misbehavior", encode(&report)),
+        ("misbehavior.sign", misbehavior_signing_bytes(&report)),
+        ("certificate", encode(&QuorumCertificate::new("prop-1", vec![vote]))),
+        ("heartbeat", encode(&Heartbeat { state: StateRoot { height: 42, root: [0x88; 32] } })),
+        ("tx_request", encode(&TxRequest { txids: vec![[0x99; 32]] })),
+        ("tx_bundle", encode(&TxBundle { txs: vec![vec![1, 2, 3], vec![]] })),
+        ("envelope", envelope::seal(vec![0xAB, 0xCD])),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    fn golden(name: &str) -> Vec<u8> {
+        let path = Path::new(VECTORS_DIR).join(format!("{}.hex", name));
+        let text = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        hex::decode(text.trim()).expect("golden file is hex")
+    }
+
+    #[test]
+    fn test_encodings_match_golden_vectors() {
+        let update = std::env::var_os("ATLAS_UPDATE_GOLDEN").is_some();
+        let mut mismatches = Vec::new();
+
+        for (name, bytes) in vectors() {
+            if update {
+                fs::write(Path::new(VECTORS_DIR).join(format!("{}.hex", name)), hex::encode(&bytes) + "\n").unwrap();
+            } else if golden(name) != bytes {
+                mismatches.push(name);
+            }
+        }
+
+        assert!(mismatches.is_empty(), "wire encoding changed for {:?}; bump PROTOCOL_VERSION and regenerate", mismatches);
+    }
+
+    #[test]
+    fn test_signing_vectors_match_golden_json() {
+        let path = Path::new(VECTORS_DIR).join("signing.json");
+        let vectors = signing_vectors();
+        if std::env::var_os("ATLAS_UPDATE_GOLDEN").is_some() {
+            fs::write(&path, serde_json::to_string_pretty(&vectors).unwrap() + "\n").unwrap();
+        }
+
+        let golden: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(golden, vectors, "signing vectors changed; bump PROTOCOL_VERSION and regenerate");
+
+        let auth = Ed25519Authenticator::from_bytes(&SIGNING_SEED).unwrap();
+        let public_key = hex::decode(golden["public_key"].as_str().unwrap()).unwrap();
+        let entries = golden["vectors"].as_array().unwrap();
+        assert_eq!(entries.len(), signing_domains::ALL.len());
+
+        for entry in entries {
+            let bytes = hex::decode(entry["signing_bytes"].as_str().unwrap()).unwrap();
+            let signature: [u8; 64] = hex::decode(entry["signature"].as_str().unwrap()).unwrap().try_into().unwrap();
+            assert!(auth.verify_with_key(&bytes, &signature, &public_key).unwrap(), "{}", entry["kind"]);
+        }
+    }
+
+    #[test]
+    fn test_golden_vectors_decode() {
+        let p: Proposal = bincode::deserialize(&golden("proposal")).unwrap();
+        assert_eq!(signing_bytes(&p), golden("proposal.sign"));
+        assert_eq!(p.request_id.as_deref(), Some("req-1"));
+
+        let v: VoteData = bincode::deserialize(&golden("vote")).unwrap();
+        assert_eq!(vote_signing_bytes(&v), golden("vote.sign"));
+
+        let vc: ViewChange = bincode::deserialize(&golden("view_change")).unwrap();
+        assert_eq!(vc.new_view, 7);
+
+        let r: MisbehaviorReport = bincode::deserialize(&golden("misbehavior")).unwrap();
+        assert_eq!(r.evidence.offender(), &node("node-b"));
+
+        let qc: QuorumCertificate = bincode::deserialize(&golden("certificate")).unwrap();
+        assert_eq!(qc.votes.len(), 1);
+
+        let hb: Heartbeat = bincode::deserialize(&golden("heartbeat")).unwrap();
+        assert_eq!(hb.state.height, 42);
+
+        let b: TxBundle = bincode::deserialize(&golden("tx_bundle")).unwrap();
+        assert_eq!(b.txs.len(), 2);
+
+        let sealed = golden("envelope");
+        assert_eq!(envelope::open(&sealed).unwrap(), (envelope::WIRE_VERSION, &[0xAB, 0xCD][..]));
+    }
+}
@@ -1,3 +1,5 @@
+use std::process::Command;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Compiling protos...");
     tonic_build::configure()
@@ -7,5 +9,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             &["../atlas-sdk/proto/atlas.proto"], // list of protos to compile
             &["../atlas-sdk/proto"], // path to search for protos
         )?;
+
+    // Commit embutido no binário (exposto via identify e GetNodeInfo).
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=ATLAS_GIT_COMMIT={}", commit);
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs/heads");
     Ok(())
 }
@@ -17,6 +17,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Ok(reply) => {
             println!("Proposal submitted successfully: {}", reply.message);
             println!("Proposal ID: {}", reply.proposal_id);
+            println!("Request ID: {}", reply.request_id);
         }
         Err(e) => {
             eprintln!("Error submitting proposal: {}", e);
@@ -14,6 +14,13 @@ fn main() {
         graph: Graph::new(),
         storage: Storage::new(),
         peer_manager: PeerManager::new(10, 5),
+        state_check: Default::default(),
+        retention: Default::default(),
+        admin_keys: Vec::new(),
+        upgrade: None,
+        jsonrpc_addr: None,
+        metrics_addr: None,
+        finality_depth: 0,
     };
     node1_config.save_to_file("node1/config.json").unwrap();
 
@@ -25,6 +32,13 @@ fn main() {
         graph: Graph::new(),
         storage: Storage::new(),
         peer_manager: PeerManager::new(10, 5),
+        state_check: Default::default(),
+        retention: Default::default(),
+        admin_keys: Vec::new(),
+        upgrade: None,
+        jsonrpc_addr: None,
+        metrics_addr: None,
+        finality_depth: 0,
     };
     node2_config.save_to_file("node2/config.json").unwrap();
 }
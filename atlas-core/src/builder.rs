@@ -34,6 +34,13 @@ pub fn init(path: Option<&str>, node_id: Option<String>, config: Option<Config>)
         graph: Graph::new(),
         storage: Storage::new(),
         peer_manager,
+        state_check: Default::default(),
+        retention: Default::default(),
+        admin_keys: Vec::new(),
+        upgrade: None,
+        jsonrpc_addr: None,
+        metrics_addr: None,
+        finality_depth: 0,
     });
 
     config.save_to_file(path.unwrap_or("config.json")).expect("Failed to save initial configuration");
@@ -1,6 +1,6 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, sync::{atomic::AtomicBool, Arc}};
 
-use tokio::sync::{oneshot, Mutex, RwLock};
+use tokio::sync::{broadcast, oneshot, Mutex, RwLock};
 use tracing::info;
 use atlas_sdk::{
     auth::Authenticator,
@@ -9,18 +9,21 @@ use atlas_sdk::{
 
 use crate::{
     config::Config, 
-    env::runtime::AtlasEnv,
+    env::{clock::{Clock, SystemClock}, retention::RetentionPolicy, runtime::AtlasEnv, storage::scrub::ScrubReport, timing::{Stage, WritePathTimings}},
+    metrics::Metrics,
     peer_manager::PeerManager, 
-    Graph, 
-    Storage
+    version::UpgradePlan,
+    Graph
 };
-use super::node::Node;
+use super::{node::Node, state_check::StateCheckMode, watchdog::Watchdog, view::{election_weights, select_leader, ViewState, PARTICIPATION_WINDOW}};
 
 
+/// Eventos de estágio guardados para assinantes lentos antes de descartar.
+pub(crate) const STAGE_EVENTS_CAPACITY: usize = 1024;
+
 // TODO: Implement retry logic for fail
 // TODO: Implement periodic health checks
 // TODO: make new tests
-// TODO: Implemente new metrics
 
 /// Simulates a distributed cluster composed of multiple nodes.
 ///
@@ -34,6 +37,45 @@ pub struct Cluster {
     pub shutdown_sender: Mutex<Option<oneshot::Sender<()>>>,
     pub auth: Arc<RwLock<dyn Authenticator>>,
     pub current_leader: Arc<RwLock<Option<NodeId>>>,
+    /// Diretório onde o nó grava seus arquivos (audit). Depende de `--network`.
+    pub data_dir: PathBuf,
+    /// Fonte de tempo do consenso; trocada por um `ManualClock` em simulações.
+    pub clock: Arc<dyn Clock>,
+    /// Tempos por etapa do caminho de escrita das propostas.
+    pub timings: Mutex<WritePathTimings>,
+    /// Reação a divergências de state root com os peers.
+    pub state_check: StateCheckMode,
+    /// Idade máxima dos logs e dumps mantidos em `data_dir`.
+    pub retention: RetentionPolicy,
+    /// Chaves públicas ed25519 (hex) autorizadas a chamar RPCs de admin.
+    pub admin_keys: Vec<String>,
+    /// Upgrade de protocolo agendado; builds antigas param de commitar na ativação.
+    pub upgrade: Option<UpgradePlan>,
+    /// Onde servir JSON-RPC enquanto este nó for o líder.
+    pub jsonrpc_addr: Option<SocketAddr>,
+    /// Onde servir as métricas Prometheus.
+    pub metrics_addr: Option<SocketAddr>,
+    /// Alturas acima de um commit antes de ele ser servido como finalizado.
+    pub finality_depth: u64,
+    /// Contadores e histogramas exportados em `/metrics`.
+    pub metrics: Arc<Metrics>,
+    /// Marcado na primeira divergência de estado detectada.
+    pub diverged: AtomicBool,
+    /// Marcado enquanto o nó está `LAG_ALARM_THRESHOLD` ou mais atrás da rede.
+    pub lagging: AtomicBool,
+    /// Marcado enquanto o consenso está parado (ver `watchdog`).
+    pub stalled: AtomicBool,
+    /// Resultado da última varredura de integridade (ver `cluster/scrub.rs`).
+    pub last_scrub: Mutex<Option<ScrubReport>>,
+    /// Último progresso do consenso observado pelo watchdog.
+    pub watchdog: Mutex<Watchdog>,
+    /// View corrente e pedidos de troca de view (timeout do líder).
+    pub view: Mutex<ViewState>,
+    /// Provas de mau comportamento já contabilizadas (infrator, proposta),
+    /// com a altura em que foram vistas (ver `cluster/misbehavior.rs`).
+    pub misbehavior_seen: Mutex<HashMap<(NodeId, String), u64>>,
+    /// Avisa cada estágio que uma proposta atinge (ver `mark_stage`).
+    pub stage_events: broadcast::Sender<(String, Stage)>,
 }
 
 impl Cluster {
@@ -54,6 +96,25 @@ impl Cluster {
             shutdown_sender: Mutex::new(None),
             auth,
             current_leader: Arc::new(RwLock::new(None)),
+            data_dir: PathBuf::from("."),
+            clock: Arc::new(SystemClock::default()),
+            timings: Mutex::new(WritePathTimings::default()),
+            state_check: StateCheckMode::default(),
+            retention: RetentionPolicy::default(),
+            admin_keys: Vec::new(),
+            upgrade: None,
+            jsonrpc_addr: None,
+            metrics_addr: None,
+            finality_depth: 0,
+            metrics: Arc::new(Metrics::default()),
+            diverged: AtomicBool::new(false),
+            lagging: AtomicBool::new(false),
+            stalled: AtomicBool::new(false),
+            last_scrub: Mutex::new(None),
+            watchdog: Mutex::new(Watchdog::default()),
+            view: Mutex::new(ViewState::default()),
+            misbehavior_seen: Mutex::new(HashMap::new()),
+            stage_events: broadcast::channel(STAGE_EVENTS_CAPACITY).0,
         }
     }
 
@@ -74,6 +135,13 @@ impl Cluster {
             graph: Graph::new(),
             storage: self.local_env.storage.read().await.clone(),
             peer_manager: self.peer_manager.read().await.clone(),
+            state_check: self.state_check,
+            retention: self.retention,
+            admin_keys: self.admin_keys.clone(),
+            upgrade: self.upgrade,
+            jsonrpc_addr: self.jsonrpc_addr,
+            metrics_addr: self.metrics_addr,
+            finality_depth: self.finality_depth,
         };
 
         config.save_to_file(path).expect("Failed to save initial configuration");
@@ -82,8 +150,11 @@ impl Cluster {
     }
 
     pub async fn elect_leader(&self) {
-        let peer_manager = self.peer_manager.read().await;
-        let active_peers = peer_manager.get_active_peers();
+        let mut active_peers = self.peer_manager.read().await.get_active_peers();
+
+        // Nós presos por votar em duplicidade não concorrem à liderança.
+        let jailed = self.local_env.engine.lock().await.jail.jailed_nodes();
+        active_peers.retain(|id| !jailed.contains(id));
 
         // Sugestão do usuário: não eleger um líder se não houver pares ativos.
         if active_peers.is_empty() {
@@ -96,20 +167,65 @@ impl Cluster {
         }
 
         let local_node_id = self.local_node.read().await.id.clone();
-        let mut candidates = active_peers;
-        candidates.insert(local_node_id.clone());
+
+        // Peso de eleição decai para quem não assina os commits recentes, para
+        // que um nó inalcançável pare de vencer e travar as rodadas. Vem só do
+        // ledger, para que todos os nós calculem os mesmos pesos.
+        active_peers.insert(local_node_id.clone());
+        let recent = self.local_env.storage.read().await.recent_signers(PARTICIPATION_WINDOW);
+        let candidates = election_weights(active_peers, &recent);
 
         // DEBUG: Imprime os candidatos em cada ciclo de eleição
         info!("[ELECTION DEBUG] Node {:?} candidates: {:?}", local_node_id, candidates);
 
-        // Algoritmo de eleição simples: o nó com o maior ID vence.
-        let new_leader = candidates.into_iter().max();
+        // Eleição determinística: a semente é o hash do último bloco e a view.
+        // Quando o líder trava, a troca de view (ver cluster/view.rs) passa a
+        // vez a outro nó mesmo sem blocos novos.
+        let view = self.current_view().await;
+        let last_block = self.local_env.storage.read().await.state_root().root;
+        let new_leader = select_leader(&candidates, &last_block, view);
 
         let mut current_leader_lock = self.current_leader.write().await;
         
         if *current_leader_lock != new_leader {
-            info!("👑 Novo líder eleito: {:?}", new_leader);
+            info!("👑 Novo líder eleito (view {}): {:?}", view, new_leader);
             *current_leader_lock = new_leader;
+            drop(current_leader_lock);
+            self.reset_leader_timer().await;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use atlas_sdk::auth::ed25519::Ed25519Authenticator;
+    use ed25519_dalek::SigningKey;
+    use rand::rngs::OsRng;
+
+    use crate::network::key_manager::node_id_for_key;
+
+    /// Chave nova e o NodeId (PeerId) derivado dela, como em produção.
+    pub(crate) fn identity() -> (NodeId, Ed25519Authenticator) {
+        let auth = Ed25519Authenticator::new(SigningKey::generate(&mut OsRng));
+        let id = node_id_for_key(&auth.public_key()).expect("chave ed25519 válida");
+        (id, auth)
+    }
+
+    /// Cluster local, sem rede, com `peers` no conjunto ativo.
+    pub(crate) fn cluster(peers: impl IntoIterator<Item = NodeId>) -> Cluster {
+        cluster_as(identity(), peers)
+    }
+
+    /// Como `cluster`, mas com a identidade local dada.
+    pub(crate) fn cluster_as((id, auth): (NodeId, Ed25519Authenticator), peers: impl IntoIterator<Item = NodeId>) -> Cluster {
+        let mut peer_manager = PeerManager::new(16, 8);
+        for peer in peers {
+            peer_manager.known_peers.insert(peer.clone(), Node::default());
+            peer_manager.active_peers.insert(peer);
         }
+
+        let env = AtlasEnv::new(Arc::new(|_| {}), Arc::new(RwLock::new(peer_manager)));
+        Cluster::new(env, id, Arc::new(RwLock::new(auth)))
     }
 }
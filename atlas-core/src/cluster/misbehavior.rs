@@ -0,0 +1,166 @@
+use tracing::{info, warn};
+
+use atlas_sdk::env::{
+    consensus::evidence::VoteEquivocation,
+    misbehavior::{misbehavior_signing_bytes, MisbehaviorReport},
+};
+
+use crate::{
+    cluster::core::Cluster,
+    error::{AtlasError, Result},
+    network::key_manager::key_matches_node,
+    peer_manager::PeerCommand,
+};
+
+/// Alturas durante as quais uma prova já contabilizada é lembrada.
+const EVIDENCE_MEMORY_HEIGHTS: u64 = 256;
+
+/// Máximo de provas lembradas, qualquer que seja a altura.
+const MAX_EVIDENCE_SEEN: usize = 1024;
+
+impl Cluster {
+    /// Assina um relatório de mau comportamento para divulgar aos peers.
+    pub(crate) async fn report_misbehavior(&self, evidence: VoteEquivocation) -> Result<MisbehaviorReport> {
+        self.note_misbehavior(&evidence).await;
+
+        let mut report = MisbehaviorReport {
+            reporter: self.local_node.read().await.id.clone(),
+            evidence,
+            signature: [0u8; 64],
+            public_key: self.auth.read().await.public_key(),
+        };
+        let sig = self.auth.read().await.sign(misbehavior_signing_bytes(&report))
+            .map_err(|e| AtlasError::Auth(format!("Signing failed: {}", e)))?;
+        report.signature = sig
+            .try_into()
+            .map_err(|_| AtlasError::Auth("assinatura inválida: tamanho incorreto".to_string()))?;
+
+        tracing::info!(target: "consensus", "EVENT:REPORT_MISBEHAVIOR offender={} proposal_id={}", report.evidence.offender(), report.evidence.first.proposal_id);
+        Ok(report)
+    }
+
+    /// Processa um relatório recebido de um peer.
+    ///
+    /// A prova anexada é verificada por completo, inclusive que os votos
+    /// foram assinados com a chave do infrator acusado; relatórios sem prova
+    /// válida são descartados. Um relatório só afeta o score do infrator,
+    /// nunca o prende: a prisão fica para quem presenciou a duplicidade.
+    pub(crate) async fn handle_misbehavior_report(&self, bytes: Vec<u8>) -> Result<()> {
+        let report: MisbehaviorReport = bincode::deserialize(&bytes)
+            .map_err(|e| AtlasError::Other(format!("decode misbehavior report: {e}")))?;
+
+        {
+            let auth = self.auth.read().await;
+            let ok = auth
+                .verify_with_key(&misbehavior_signing_bytes(&report), &report.signature, &report.public_key)
+                .map_err(|e| AtlasError::Auth(format!("verify failed: {e}")))?;
+            if !ok || !key_matches_node(&report.reporter, &report.public_key) {
+                return Err(AtlasError::Auth(format!("assinatura inválida no relatório de {}", report.reporter)));
+            }
+            report.evidence.verify(&*auth)
+                .map_err(|e| AtlasError::Auth(format!("prova inválida no relatório de {}: {e}", report.reporter)))?;
+        }
+        // `verify` garante que os dois votos usam a mesma chave; ela precisa ser a do acusado.
+        let offender = report.evidence.offender();
+        if !key_matches_node(offender, &report.evidence.first.public_key) {
+            return Err(AtlasError::Auth(format!("prova no relatório de {} não foi assinada por {}", report.reporter, offender)));
+        }
+
+        tracing::info!(target: "consensus", "EVENT:RECEIVE_MISBEHAVIOR reporter={} offender={} proposal_id={}", report.reporter, report.evidence.offender(), report.evidence.first.proposal_id);
+        self.note_misbehavior(&report.evidence).await;
+        Ok(())
+    }
+
+    /// Conta a prova contra o infrator uma única vez, por mais que seja relatada.
+    ///
+    /// Provas são lembradas por `EVIDENCE_MEMORY_HEIGHTS` alturas, até no
+    /// máximo `MAX_EVIDENCE_SEEN` delas.
+    async fn note_misbehavior(&self, evidence: &VoteEquivocation) {
+        let offender = evidence.offender().clone();
+        let key = (offender.clone(), evidence.first.proposal_id.clone());
+        let height = self.local_env.storage.read().await.state_root().height;
+        {
+            let mut seen = self.misbehavior_seen.lock().await;
+            if seen.contains_key(&key) {
+                return;
+            }
+            seen.retain(|_, seen_at| *seen_at + EVIDENCE_MEMORY_HEIGHTS > height);
+            if seen.len() >= MAX_EVIDENCE_SEEN {
+                if let Some(oldest) = seen.iter().min_by_key(|(_, seen_at)| **seen_at).map(|(k, _)| k.clone()) {
+                    seen.remove(&oldest);
+                }
+            }
+            seen.insert(key, height);
+        }
+
+        warn!("🚫 Prova de voto duplo de [{}] na proposta [{}]", offender, evidence.first.proposal_id);
+        self.peer_manager.write().await.handle_command(PeerCommand::Misbehaved(offender.clone()));
+        info!("📉 Score de [{}] rebaixado", offender);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atlas_sdk::{
+        auth::{ed25519::Ed25519Authenticator, Authenticator},
+        env::{consensus::types::Vote, vote_data::{vote_signing_bytes, VoteData}},
+        utils::NodeId,
+    };
+
+    use crate::cluster::core::tests::{cluster, identity};
+
+    fn signed_vote(voter: &NodeId, auth: &Ed25519Authenticator, vote: Vote) -> VoteData {
+        let mut data = VoteData {
+            proposal_id: "p1".into(),
+            vote,
+            voter: voter.clone(),
+            signature: [0u8; 64],
+            public_key: auth.public_key(),
+        };
+        data.signature = auth.sign(vote_signing_bytes(&data)).unwrap().try_into().unwrap();
+        data
+    }
+
+    fn report(offender: &NodeId, signer: &Ed25519Authenticator) -> Vec<u8> {
+        let (reporter, reporter_auth) = identity();
+        let mut report = MisbehaviorReport {
+            reporter,
+            evidence: VoteEquivocation {
+                first: signed_vote(offender, signer, Vote::Yes),
+                second: signed_vote(offender, signer, Vote::No),
+            },
+            signature: [0u8; 64],
+            public_key: reporter_auth.public_key(),
+        };
+        report.signature = reporter_auth.sign(misbehavior_signing_bytes(&report)).unwrap().try_into().unwrap();
+        report.bytes()
+    }
+
+    #[tokio::test]
+    async fn test_fabricated_evidence_does_not_demote_the_accused() {
+        let (honest, honest_auth) = identity();
+        let node = cluster([honest.clone()]);
+
+        // Votos conflitantes assinados com outra chave em nome de `honest`.
+        let (_, forger) = identity();
+        assert!(node.handle_misbehavior_report(report(&honest, &forger)).await.is_err());
+        assert!(node.peer_manager.read().await.active_peers.contains(&honest));
+
+        node.handle_misbehavior_report(report(&honest, &honest_auth)).await.unwrap();
+        let peers = node.peer_manager.read().await;
+        assert!(!peers.active_peers.contains(&honest));
+        assert_eq!(peers.misbehavior.get(&honest), Some(&1));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_offenders_are_not_tracked() {
+        let node = cluster([]);
+        for _ in 0..4 {
+            let (stranger, auth) = identity();
+            node.handle_misbehavior_report(report(&stranger, &auth)).await.unwrap();
+        }
+        assert!(node.peer_manager.read().await.misbehavior.is_empty());
+        assert_eq!(node.misbehavior_seen.lock().await.len(), 4);
+    }
+}
@@ -1,7 +1,12 @@
 pub mod builder;
 pub mod core;
+pub mod misbehavior;
 pub mod node;
 pub mod peers;
 pub mod proposals;
+pub mod scrub;
 pub mod shutdown;
+pub mod state_check;
+pub mod view;
 pub mod voting;
+pub mod watchdog;
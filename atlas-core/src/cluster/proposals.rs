@@ -1,4 +1,5 @@
-use crate::{cluster::core::Cluster, env::proposal::Proposal, network::p2p::adapter::AdapterCmd, error::{AtlasError, Result}};
+use crate::{cluster::core::Cluster, env::{proposal::{Proposal, SealedProposal}, storage::{Attestation, ValidatorStats, VoteRecord}, timing::Stage}, network::p2p::adapter::AdapterCmd, error::{AtlasError, Result}};
+use atlas_sdk::{env::consensus::{certificate::QuorumCertificate, types::ConsensusResult}, utils::NodeId};
 use tracing::{info, warn};
 
 const PROPOSAL_TOPIC: &str = "atlas/proposal/v1";
@@ -9,24 +10,56 @@ impl Cluster {
     /// Esta função adiciona a proposta ao pool de consenso local, a serializa
     /// e, em seguida, retorna um `AdapterCmd::Publish` que pode ser enviado
     /// pela camada de rede para disseminar a proposta via gossip.
-    pub async fn submit_proposal(&self, proposal: Proposal) -> Result<AdapterCmd> {
-        // 1. Adicionar a proposta ao nosso próprio pool de consenso primeiro.
-        self.add_proposal(proposal.clone()).await?;
+    #[tracing::instrument(name = "pool", skip_all, fields(proposal_id = %sealed.proposal().id))]
+    pub async fn submit_proposal(&self, sealed: SealedProposal) -> Result<AdapterCmd> {
+        #[cfg(feature = "fault-injection")]
+        crate::fault::faults().delay_proposal().await;
 
-        // 2. Serializar a proposta para enviar pela rede.
-        let bytes = bincode::serialize(&proposal)
-            .map_err(|e| AtlasError::Other(format!("failed to serialize proposal: {e}")))?;
+        // 1. Adicionar a proposta ao nosso próprio pool de consenso primeiro.
+        //    A serialização para a rede já foi feita ao selar a proposta.
+        let (proposal, bytes) = sealed.into_parts();
+        let id = proposal.id.clone();
+        self.add_proposal(proposal).await?;
+        self.mark_stage(&id, Stage::Pooled).await;
 
-        // 3. Criar e retornar o comando para publicação, delegando o envio.
+        // 2. Criar e retornar o comando para publicação, delegando o envio.
         Ok(AdapterCmd::Publish {
             topic: PROPOSAL_TOPIC.into(),
             data: bytes,
         })
     }
 
+    /// Registra o estágio nas métricas de latência e avisa os assinantes de `stage_events`.
+    pub(crate) async fn mark_stage(&self, id: &str, stage: Stage) {
+        self.timings.lock().await.mark(id, stage);
+        // sem assinantes o envio falha, e tudo bem
+        let _ = self.stage_events.send((id.to_string(), stage));
+    }
+
+    /// Verdadeiro se a proposta já atingiu `stage`, segundo as marcas
+    /// recentes ou, para `Stored`, o resultado gravado no storage.
+    pub(crate) async fn reached_stage(&self, id: &str, stage: Stage) -> bool {
+        if self.timings.lock().await.record(id).iter().any(|(reached, _)| *reached == stage) {
+            return true;
+        }
+        stage == Stage::Stored && self.find_result(id).await.is_some_and(|r| r.approved)
+    }
+
+    /// Coloca a proposta no pool e a registra no storage para as estatísticas do proponente.
+    ///
+    /// Recusa propostas já commitadas: um replay voltaria a ser votado e
+    /// commitado, contando o commit duas vezes.
     pub(super) async fn add_proposal(&self, proposal: Proposal) -> Result<()> {
+        {
+            let mut storage = self.local_env.storage.write().await;
+            if storage.results.get(&proposal.id).is_some_and(|r| r.approved) {
+                tracing::info!(target: "consensus", "EVENT:REPLAYED_PROPOSAL id={}", proposal.id);
+                return Err(AtlasError::Consensus(format!("proposta {} já commitada", proposal.id)));
+            }
+            storage.log_pooled(&proposal);
+        }
         self.local_env.engine.lock().await
-            .add_proposal(proposal.clone());
+            .add_proposal(proposal);
 
         Ok(())
     }
@@ -36,17 +69,96 @@ impl Cluster {
         Ok(proposals.values().cloned().collect())
     }
 
-    pub(crate) async fn handle_proposal(&self, bytes: Vec<u8>) -> Result<()> {
-        let proposal: Proposal = bincode::deserialize(&bytes)
-            .map_err(|e| AtlasError::Other(format!("decode proposal: {e}")))?;
+    /// Busca uma proposta pelo ID, primeiro no storage (commitadas) e depois no pool.
+    ///
+    /// Retorna a proposta e se ela já foi commitada.
+    pub(crate) async fn find_proposal(&self, id: &str) -> Option<(Proposal, bool)> {
+        if let Some(p) = self.local_env.storage.read().await.proposals.iter().find(|p| p.id == id) {
+            return Some((p.clone(), true));
+        }
+        self.local_env.engine.lock().await
+            .pool.find_by_id(id)
+            .cloned()
+            .map(|p| (p, false))
+    }
+
+    /// Busca uma proposta commitada junto com o certificado de quórum que a finalizou.
+    pub(crate) async fn get_proposal_with_qc(&self, id: &str) -> Option<(Proposal, QuorumCertificate)> {
+        let storage = self.local_env.storage.read().await;
+        let proposal = storage.proposals.iter().find(|p| p.id == id)?.clone();
+        let certificate = storage.certificates.get(id)?.clone();
+        Some((proposal, certificate))
+    }
+
+    /// Propostas aprovadas nas alturas `from..=to` (1 = primeira), com seus certificados.
+    pub(crate) async fn blocks_between(&self, from: u64, to: u64) -> Vec<(u64, Proposal, Option<QuorumCertificate>)> {
+        let storage = self.local_env.storage.read().await;
+        storage.chain()
+            .skip_while(|(h, _)| *h < from)
+            .take_while(|(h, _)| *h <= to)
+            .map(|(h, p)| (h, p.clone(), storage.certificates.get(&p.id).cloned()))
+            .collect()
+    }
+
+    /// Proposta aprovada na altura `height`, com seu certificado.
+    pub(crate) async fn block_at(&self, height: u64) -> Option<(Proposal, Option<QuorumCertificate>)> {
+        let storage = self.local_env.storage.read().await;
+        let block = storage.chain()
+            .find(|(h, _)| *h == height)
+            .map(|(_, p)| (p.clone(), storage.certificates.get(&p.id).cloned()));
+        block
+    }
+
+    /// Maior altura servida como finalizada: `finality_depth` abaixo do topo.
+    pub(crate) async fn finalized_height(&self) -> u64 {
+        let head = self.local_env.storage.read().await.state_root().height;
+        head.saturating_sub(self.finality_depth)
+    }
+
+    /// Altura de uma proposta aprovada.
+    pub(crate) async fn height_of(&self, id: &str) -> Option<u64> {
+        self.local_env.storage.read().await.chain().find(|(_, p)| p.id == id).map(|(h, _)| h)
+    }
+
+    /// Quem assinou o quórum de cada proposta commitada, em ordem de commit.
+    pub(crate) async fn attestations(&self) -> Vec<Attestation> {
+        self.local_env.storage.read().await.attestations()
+    }
+
+    /// Histórico de um validador no ledger local, com relatórios de mau
+    /// comportamento recebidos e se está preso agora.
+    pub(crate) async fn validator_performance(&self, node: &NodeId) -> (ValidatorStats, u32, bool) {
+        let stats = self.local_env.storage.read().await.validator_stats(node);
+        let reports = self.peer_manager.read().await.misbehavior.get(node).copied().unwrap_or(0);
+        let jailed = self.local_env.engine.lock().await.jail.is_jailed(node);
+        (stats, reports, jailed)
+    }
+
+    /// Votos recebidos para uma proposta, em ordem de chegada.
+    pub(crate) async fn vote_log(&self, id: &str) -> Vec<VoteRecord> {
+        self.local_env.storage.read().await.vote_log.get(id).cloned().unwrap_or_default()
+    }
+
+    /// Busca o resultado de consenso registrado para uma proposta.
+    pub(crate) async fn find_result(&self, id: &str) -> Option<ConsensusResult> {
+        self.local_env.storage.read().await.results.get(id).cloned()
+    }
+
+    #[tracing::instrument(
+        name = "receive_proposal",
+        skip_all,
+        fields(proposal_id = %sealed.proposal().id, proposer = %sealed.proposal().proposer),
+    )]
+    pub(crate) async fn handle_proposal(&self, sealed: SealedProposal) -> Result<()> {
+        let proposal = sealed.proposal();
 
         info!("📩 Proposta recebida: {:?}", proposal);
-        tracing::info!(target: "consensus", "EVENT:RECEIVE_PROPOSAL id={} from={}", proposal.id, proposal.proposer);
+        self.mark_stage(&proposal.id, Stage::Received).await;
+        tracing::info!(target: "consensus", "EVENT:RECEIVE_PROPOSAL id={} from={} request_id={}", proposal.id, proposal.proposer, proposal.request_id.as_deref().unwrap_or("-"));
 
-        // bytes canônicos para assinatura
-        let sign_bytes = crate::env::proposal::signing_bytes(&proposal);
+        // bytes canônicos para assinatura, calculados uma vez ao selar
         let ok = self.auth.read().await
-            .verify_with_key(sign_bytes, &proposal.signature, &proposal.public_key)
+            .verify_with_key(sealed.signing_bytes(), &proposal.signature, &proposal.public_key)
             .map_err(|e| AtlasError::Auth(format!("verify failed: {e}")))?;
         
         if !ok { 
@@ -58,27 +170,121 @@ impl Cluster {
         info!("✅ Assinatura verificada com sucesso para proposta {} (Proposer: {})", proposal.id, proposal.proposer);
         tracing::info!(target: "consensus", "EVENT:VERIFY_PROPOSAL_OK id={}", proposal.id);
 
-        self.local_env.engine.lock().await.add_proposal(proposal);
+        let id = proposal.id.clone();
+        self.add_proposal(sealed.into_proposal()).await?;
+        self.mark_stage(&id, Stage::Pooled).await;
         Ok(())
     }
 
-    pub(crate) async fn evaluate_proposals(&self) -> Result<Vec<atlas_sdk::env::consensus::types::ConsensusResult>> {
+    pub(crate) async fn evaluate_proposals(&self) -> Result<Vec<ConsensusResult>> {
         info!("🗳️ Avaliando consenso");
         let results = self.local_env.engine.lock().await.evaluate_proposals().await;
         Ok(results)
     }
     
-    pub(crate) async fn commit_proposal(&self, result: atlas_sdk::env::consensus::types::ConsensusResult) -> Result<()> {
+    #[tracing::instrument(
+        name = "commit",
+        skip_all,
+        fields(proposal_id = %result.proposal_id, approved = result.approved, height = tracing::field::Empty),
+    )]
+    pub(crate) async fn commit_proposal(&self, result: ConsensusResult) -> Result<()> {
         info!("💾 Committing proposal {} (Approved: {})", result.proposal_id, result.approved);
+
+        if let Some(plan) = self.upgrade {
+            let height = self.local_env.storage.read().await.state_root().height + 1;
+            if result.approved && !plan.allows_commit(height) {
+                warn!("⛔ Altura {} exige o protocolo {}; atualize o nó", height, plan.protocol_version);
+                tracing::error!(target: "consensus", "EVENT:UPGRADE_HALT height={} required={} running={}", height, plan.protocol_version, crate::version::PROTOCOL_VERSION);
+                return Err(AtlasError::Other(format!(
+                    "commit na altura {} exige protocolo {} (este nó fala {})",
+                    height, plan.protocol_version, crate::version::PROTOCOL_VERSION
+                )));
+            }
+        }
         
-        // 1. Log result to in-memory storage
-        self.local_env.storage.write().await.log_result(&result.proposal_id, result.clone());
+        // 1. Log proposal, result and quorum certificate to in-memory storage
+        let (proposal, pruned, certificate) = {
+            let mut engine = self.local_env.engine.lock().await;
+            engine.on_commit();
+            let certificate = result.approved.then(|| {
+                QuorumCertificate::new(&result.proposal_id, engine.registry.signed_votes(&result.proposal_id))
+            });
+            // a partir daqui a proposta vive só no storage
+            let (proposal, pruned) = engine.finalize_proposal(&result.proposal_id);
+            (proposal, pruned, certificate)
+        };
+        let request_id = proposal.as_ref().and_then(|p| p.request_id.clone());
+        tracing::info!(target: "consensus", "EVENT:STORE id={} request_id={}", result.proposal_id, request_id.as_deref().unwrap_or("-"));
+
+        #[cfg(feature = "fault-injection")]
+        crate::fault::faults().wait_storage().await;
+
+        {
+            let mut storage = self.local_env.storage.write().await;
+            if let Some(proposal) = proposal {
+                if !storage.proposals.iter().any(|p| p.id == proposal.id) {
+                    storage.log_proposal(proposal);
+                }
+            }
+            storage.log_result(&result.proposal_id, result.clone());
+            // o proponente agora está em `proposals`; as descartadas saem das estatísticas
+            storage.forget_pooled(std::iter::once(&result.proposal_id).chain(&pruned));
+            if let Some(certificate) = certificate {
+                storage.log_certificate(certificate);
+            }
+            tracing::Span::current().record("height", storage.state_root().height);
+        }
+        self.mark_stage(&result.proposal_id, Stage::Stored).await;
 
         // 2. Persist to disk (simple audit file)
         let node_id = self.local_node.read().await.id.clone();
-        let filename = format!("audit-{}.json", node_id);
-        self.local_env.export_audit(&filename).await;
+        let filename = self.data_dir.join(format!("audit-{}.json", node_id));
+        let started = std::time::Instant::now();
+        self.local_env.export_audit(&filename.to_string_lossy()).await?;
+        self.metrics.record_audit_write(started.elapsed());
+        self.metrics.commits.inc();
+        self.mark_stage(&result.proposal_id, Stage::Persisted).await;
+
+        self.note_progress().await;
+
+        #[cfg(feature = "fault-injection")]
+        crate::fault::faults().record_commit();
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atlas_sdk::auth::Authenticator;
+
+    use crate::cluster::core::tests::{cluster_as, identity};
+    use crate::env::proposal::signing_bytes;
+
+    #[tokio::test]
+    async fn test_replayed_committed_proposal_is_not_pooled_again() {
+        let (id, auth) = identity();
+        let mut proposal = Proposal {
+            id: "p1".to_string(),
+            proposer: id.clone(),
+            content: "{}".to_string(),
+            parent: None,
+            signature: [0u8; 64],
+            public_key: auth.public_key(),
+            request_id: None,
+        };
+        proposal.signature = auth.sign(signing_bytes(&proposal)).unwrap().try_into().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let mut node = cluster_as((id, auth), []);
+        node.data_dir = dir.path().to_path_buf();
+
+        node.handle_proposal(SealedProposal::new(proposal.clone())).await.unwrap();
+        node.commit_proposal(ConsensusResult { proposal_id: "p1".to_string(), approved: true, votes_received: 1 }).await.unwrap();
+        assert!(node.local_env.engine.lock().await.pool.all().is_empty());
+        assert!(node.local_env.storage.read().await.pooled.is_empty());
+
+        assert!(node.handle_proposal(SealedProposal::new(proposal)).await.is_err());
+        assert!(node.local_env.engine.lock().await.pool.all().is_empty());
+        assert_eq!(node.local_env.storage.read().await.state_root().height, 1);
+    }
+}
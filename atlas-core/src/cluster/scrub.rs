@@ -0,0 +1,50 @@
+use tracing::{info, warn};
+
+use crate::{
+    cluster::core::Cluster,
+    env::storage::scrub::{ScrubIssue, ScrubReport},
+    error::Result,
+};
+
+impl Cluster {
+    /// Executa uma varredura de integridade sobre o storage local.
+    ///
+    /// Além das checagens de consistência do próprio `Storage`, verifica
+    /// novamente a assinatura de cada proposta armazenada. As discrepâncias
+    /// são registradas no log e o relatório fica guardado em `last_scrub`,
+    /// de onde saem a métrica `scrub_issues` e o `get_node_info`.
+    pub(crate) async fn scrub_storage(&self) -> Result<ScrubReport> {
+        let (mut report, proposals) = {
+            let storage = self.local_env.storage.read().await;
+            (storage.scrub(), storage.proposals.clone())
+        };
+
+        for proposal in &proposals {
+            let sign_bytes = crate::env::proposal::signing_bytes(proposal);
+            let ok = self.auth.read().await
+                .verify_with_key(&sign_bytes, &proposal.signature, &proposal.public_key)
+                .unwrap_or(false);
+
+            if !ok {
+                report.issues.push(ScrubIssue::InvalidSignature {
+                    proposal_id: proposal.id.clone(),
+                });
+            }
+
+            // Varredura de baixa prioridade: cede a vez a cada proposta.
+            tokio::task::yield_now().await;
+        }
+
+        if report.is_clean() {
+            info!("🧹 Scrub OK ({} propostas verificadas)", report.proposals_checked);
+        } else {
+            for issue in &report.issues {
+                warn!("🧹 Scrub encontrou inconsistência: {}", issue);
+            }
+        }
+        tracing::info!(target: "consensus", "EVENT:SCRUB checked={} issues={}", report.proposals_checked, report.issues.len());
+        *self.last_scrub.lock().await = Some(report.clone());
+
+        Ok(report)
+    }
+}
@@ -1,6 +1,7 @@
 use crate::cluster::core::Cluster;
 
 impl Cluster {
+    #[allow(dead_code)]
     pub(super) async fn shutdown_grpc(&self) {
         if let Some(sender) = self.shutdown_sender.lock().await.take() {
             let _ = sender.send(());
@@ -0,0 +1,179 @@
+use std::sync::atomic::Ordering;
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+
+use atlas_sdk::utils::NodeId;
+
+use crate::{
+    cluster::core::Cluster,
+    env::storage::{audit::AuditData, StateRoot},
+    error::{AtlasError, Result},
+    network::p2p::protocol::Heartbeat,
+    peer_manager::PeerCommand,
+};
+
+/// Atraso (em propostas aprovadas) em relação à mediana da rede que dispara o alarme.
+pub const LAG_ALARM_THRESHOLD: u64 = 10;
+
+/// O que fazer quando o estado local diverge do estado anunciado por um peer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateCheckMode {
+    /// Não compara os state roots.
+    Off,
+    /// Registra o alerta e grava o dump de diagnóstico, mas segue produzindo propostas.
+    #[default]
+    Warn,
+    /// Como `Warn`, e suspende a produção de propostas até o nó ser reiniciado.
+    Halt,
+}
+
+/// Dump gravado em disco na primeira divergência detectada.
+#[derive(Debug, Serialize)]
+struct DivergenceDump<'a> {
+    local_node: &'a NodeId,
+    local: StateRoot,
+    peer: &'a NodeId,
+    peer_state: StateRoot,
+    audit: AuditData,
+}
+
+impl Cluster {
+    /// Heartbeat com o state root atual, para ser anunciado aos peers.
+    pub(crate) async fn heartbeat(&self) -> Heartbeat {
+        Heartbeat { state: self.local_env.storage.read().await.state_root() }
+    }
+
+    /// Compara o state root anunciado por `from` com o local.
+    ///
+    /// Só compara quando ambos estão na mesma altura; um peer adiantado ou
+    /// atrasado ainda não é divergência. Retorna `true` se houve divergência.
+    pub(crate) async fn check_peer_state(&self, from: &NodeId, heartbeat: &Heartbeat) -> Result<bool> {
+        if self.state_check == StateCheckMode::Off {
+            return Ok(false);
+        }
+
+        let local = self.local_env.storage.read().await.state_root();
+        let peer_state = heartbeat.state;
+        if local.height != peer_state.height || local.root == peer_state.root {
+            return Ok(false);
+        }
+
+        error!(
+            "🚨 DIVERGÊNCIA DE ESTADO com {} na altura {}: local={} peer={}",
+            from, local.height, hex::encode(local.root), hex::encode(peer_state.root)
+        );
+        tracing::error!(target: "consensus", "EVENT:STATE_DIVERGENCE peer={} height={} local={} remote={}",
+            from, local.height, hex::encode(local.root), hex::encode(peer_state.root));
+
+        // Dump apenas na primeira divergência, para não inundar o disco.
+        if !self.diverged.swap(true, Ordering::SeqCst) {
+            self.dump_divergence(from, local, peer_state).await?;
+            if self.state_check == StateCheckMode::Halt {
+                error!("🛑 Produção de propostas suspensa até a divergência ser investigada");
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Registra a altura anunciada por `from` e reavalia o alarme de atraso.
+    pub(crate) async fn track_peer_height(&self, from: &NodeId, height: u64) {
+        self.peer_manager.write().await.handle_command(PeerCommand::Height(from.clone(), height));
+
+        let behind = self.blocks_behind().await;
+        let lagging = behind >= LAG_ALARM_THRESHOLD;
+        if self.lagging.swap(lagging, Ordering::SeqCst) == lagging {
+            return;
+        }
+
+        if lagging {
+            warn!("🐢 Nó atrasado: {} propostas atrás da mediana da rede", behind);
+            tracing::warn!(target: "consensus", "EVENT:LAG_ALARM behind={} threshold={}", behind, LAG_ALARM_THRESHOLD);
+        } else {
+            tracing::info!(target: "consensus", "EVENT:LAG_RECOVERED behind={}", behind);
+        }
+    }
+
+    /// Quantas propostas aprovadas o nó está atrás da mediana dos peers.
+    pub async fn blocks_behind(&self) -> u64 {
+        let local = self.local_env.storage.read().await.state_root().height;
+        self.network_height().await.unwrap_or(local).saturating_sub(local)
+    }
+
+    /// Mediana das alturas anunciadas pelos peers, se algum já anunciou.
+    pub async fn network_height(&self) -> Option<u64> {
+        self.peer_manager.read().await.network_height()
+    }
+
+    /// Verdadeiro quando a produção de propostas foi suspensa por divergência.
+    pub fn is_halted(&self) -> bool {
+        self.state_check == StateCheckMode::Halt && self.diverged.load(Ordering::SeqCst)
+    }
+
+    async fn dump_divergence(&self, peer: &NodeId, local: StateRoot, peer_state: StateRoot) -> Result<()> {
+        let local_node = self.local_node.read().await.id.clone();
+        let dump = DivergenceDump {
+            local_node: &local_node,
+            local,
+            peer,
+            peer_state,
+            audit: self.local_env.storage.read().await.to_audit(),
+        };
+
+        let secs = self.clock.now_ms() / 1000;
+        let path = self.data_dir.join(format!("divergence-{}-{}.json", local_node, secs));
+
+        let json = serde_json::to_vec_pretty(&dump)
+            .map_err(|e| AtlasError::Storage(format!("divergence dump: {e}")))?;
+        std::fs::write(&path, json)?;
+        warn!("📄 Dump de diagnóstico gravado em {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster::{core::tests::cluster, node::Node};
+
+    #[tokio::test]
+    async fn test_lag_alarm_follows_the_network_height() {
+        let peers: Vec<NodeId> = ["a", "b", "c"].iter().map(|id| NodeId(id.to_string())).collect();
+        let node = cluster(peers.clone());
+        assert_eq!(node.network_height().await, None);
+        assert_eq!(node.blocks_behind().await, 0);
+
+        // Um único peer muito adiantado não move a mediana.
+        node.track_peer_height(&peers[0], 500).await;
+        node.track_peer_height(&peers[1], 1).await;
+        node.track_peer_height(&peers[2], 2).await;
+        assert_eq!(node.blocks_behind().await, 2);
+        assert!(!node.lagging.load(Ordering::SeqCst));
+
+        node.track_peer_height(&peers[1], LAG_ALARM_THRESHOLD + 5).await;
+        assert_eq!(node.blocks_behind().await, LAG_ALARM_THRESHOLD + 5);
+        assert!(node.lagging.load(Ordering::SeqCst));
+
+        node.track_peer_height(&peers[1], 3).await;
+        assert!(!node.lagging.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_stale_peers_do_not_raise_the_lag_alarm() {
+        let peer = NodeId("a".into());
+        let node = cluster([peer.clone()]);
+        node.track_peer_height(&peer, LAG_ALARM_THRESHOLD * 2).await;
+        assert!(node.lagging.load(Ordering::SeqCst));
+
+        node.peer_manager.write().await.handle_command(PeerCommand::Drop(peer));
+        node.track_peer_height(&NodeId("stranger".into()), LAG_ALARM_THRESHOLD * 2).await;
+        assert_eq!(node.network_height().await, None);
+        assert!(!node.lagging.load(Ordering::SeqCst));
+
+        node.peer_manager.write().await.handle_command(PeerCommand::Register(NodeId("b".into()), Node::default()));
+        node.track_peer_height(&NodeId("b".into()), 0).await;
+        assert_eq!(node.network_height().await, Some(0));
+    }
+}
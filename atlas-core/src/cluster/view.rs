@@ -0,0 +1,461 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use atlas_sdk::utils::NodeId;
+
+use crate::{
+    cluster::core::Cluster,
+    env::view_change::{view_change_signing_bytes, ViewChange},
+    error::{AtlasError, Result},
+    network::key_manager::key_matches_node,
+};
+
+/// Peso de eleição de um nó que vem assinando os commits.
+pub const BASE_ELECTION_WEIGHT: u64 = 1 << 10;
+
+/// Commits recentes olhados para medir a participação dos candidatos.
+pub const PARTICIPATION_WINDOW: usize = 8;
+
+/// Commits seguidos sem a assinatura do candidato tolerados antes de o peso cair.
+pub const MISSED_COMMITS_GRACE: u32 = 2;
+
+/// Estado da view corrente e dos pedidos de troca de view em andamento.
+#[derive(Debug)]
+pub struct ViewState {
+    /// View corrente; entra na semente da eleição.
+    pub view: u64,
+    /// Maior view acima da corrente pedida por cada votante.
+    requested: HashMap<NodeId, u64>,
+    /// Última vez que o líder corrente deu sinal de vida (`Clock::monotonic`).
+    leader_seen: Duration,
+    /// Maior view para a qual este nó já votou.
+    voted_for: u64,
+    /// Último pedido de troca assinado por este nó. É redivulgado
+    /// periodicamente para que nós reiniciados ou atrasados alcancem a view
+    /// do cluster.
+    own_vote: Option<ViewChange>,
+}
+
+impl Default for ViewState {
+    fn default() -> Self {
+        Self {
+            view: 0,
+            requested: HashMap::new(),
+            leader_seen: Duration::ZERO,
+            voted_for: 0,
+            own_vote: None,
+        }
+    }
+}
+
+/// Maior view apoiada por pelo menos `quorum` votantes.
+///
+/// Um pedido pela view `v` apoia toda view até `v`: quem pede `v` já
+/// abandonou os líderes das anteriores. Assim um nó atrasado salta direto
+/// para a view em que o cluster está.
+fn quorum_view(requested: impl IntoIterator<Item = u64>, quorum: usize) -> Option<u64> {
+    let mut views: Vec<u64> = requested.into_iter().collect();
+    views.sort_unstable_by(|a, b| b.cmp(a));
+    views.get(quorum.checked_sub(1)?).copied()
+}
+
+/// Peso de eleição de um candidato que não assinou os últimos `missed` commits.
+///
+/// Após `MISSED_COMMITS_GRACE` commits perdidos o peso cai pela metade a
+/// cada commit adicional, até zero. Só o peso de eleição decai; o nó
+/// continua sendo um peer ativo.
+pub fn election_weight(missed: u32) -> u64 {
+    let decay = missed.saturating_sub(MISSED_COMMITS_GRACE);
+    BASE_ELECTION_WEIGHT.checked_shr(decay).unwrap_or(0)
+}
+
+/// Pesos de eleição a partir dos signatários dos commits recentes (mais novo primeiro).
+///
+/// Usa só o ledger commitado, nunca relógio ou heartbeats locais: nós com o
+/// mesmo estado e os mesmos candidatos chegam aos mesmos pesos, inclusive
+/// para si mesmos.
+pub fn election_weights(
+    candidates: impl IntoIterator<Item = NodeId>,
+    recent_signers: &[HashSet<NodeId>],
+) -> HashMap<NodeId, u64> {
+    candidates
+        .into_iter()
+        .map(|id| {
+            let missed = recent_signers.iter().take_while(|signers| !signers.contains(&id)).count();
+            let weight = election_weight(missed as u32);
+            (id, weight)
+        })
+        .collect()
+}
+
+/// Escolhe o líder entre os candidatos (com seus pesos) usando como semente
+/// o hash do último bloco e a view.
+///
+/// Todos os nós com os mesmos candidatos, pesos, último bloco e view elegem
+/// o mesmo líder; avançar a view faz a liderança passar a outro candidato
+/// mesmo sem novos blocos. Se todos os pesos forem zero, a escolha é uniforme.
+pub fn select_leader(candidates: &HashMap<NodeId, u64>, last_block: &[u8; 32], view: u64) -> Option<NodeId> {
+    let mut sorted: Vec<(&NodeId, u64)> = candidates.iter().map(|(id, w)| (id, *w)).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort();
+    if sorted.iter().all(|(_, w)| *w == 0) {
+        sorted.iter_mut().for_each(|(_, w)| *w = 1);
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(last_block);
+    hasher.update(view.to_le_bytes());
+    let digest = hasher.finalize();
+    let seed = u64::from_le_bytes(digest[..8].try_into().expect("sha256 tem 32 bytes"));
+    let total: u64 = sorted.iter().map(|(_, w)| w).sum();
+
+    let mut ticket = seed % total;
+    for (id, weight) in sorted {
+        if ticket < weight {
+            return Some(id.clone());
+        }
+        ticket -= weight;
+    }
+    None
+}
+
+impl Cluster {
+    /// View corrente.
+    pub async fn current_view(&self) -> u64 {
+        self.view.lock().await.view
+    }
+
+    /// Registra atividade de `from`; se for o líder, reinicia o timeout.
+    pub(crate) async fn note_leader_activity(&self, from: &NodeId) {
+        if self.current_leader.read().await.as_ref() == Some(from) {
+            self.view.lock().await.leader_seen = self.clock.monotonic();
+        }
+    }
+
+    /// Reinicia o timeout do líder (ex.: logo após uma eleição).
+    pub(crate) async fn reset_leader_timer(&self) {
+        self.view.lock().await.leader_seen = self.clock.monotonic();
+    }
+
+    /// Verdadeiro se há um líder remoto que não dá sinal de vida há mais de `timeout`.
+    pub(crate) async fn leader_timed_out(&self, timeout: Duration) -> bool {
+        let leader = self.current_leader.read().await.clone();
+        let local = self.local_node.read().await.id.clone();
+        match leader {
+            Some(leader) if leader != local => {
+                self.clock.monotonic().saturating_sub(self.view.lock().await.leader_seen) > timeout
+            }
+            _ => false,
+        }
+    }
+
+    /// Último pedido de troca assinado por este nó, para redivulgar.
+    pub(crate) async fn view_vote(&self) -> Option<ViewChange> {
+        self.view.lock().await.own_vote.clone()
+    }
+
+    async fn sign_view_change(&self, new_view: u64) -> Result<ViewChange> {
+        let mut vc = ViewChange {
+            new_view,
+            voter: self.local_node.read().await.id.clone(),
+            signature: [0u8; 64],
+            public_key: self.auth.read().await.public_key(),
+        };
+        let sig = self.auth.read().await.sign(view_change_signing_bytes(&vc))
+            .map_err(|e| AtlasError::Auth(format!("Signing failed: {}", e)))?;
+        vc.signature = sig
+            .try_into()
+            .map_err(|_| AtlasError::Auth("assinatura inválida: tamanho incorreto".to_string()))?;
+        Ok(vc)
+    }
+
+    /// Guarda `vc` como o voto a redivulgar, se for mais novo que o atual.
+    async fn keep_own_vote(&self, vc: &ViewChange) {
+        let mut state = self.view.lock().await;
+        if state.own_vote.as_ref().is_none_or(|own| own.new_view < vc.new_view) {
+            state.own_vote = Some(vc.clone());
+        }
+    }
+
+    /// Monta e assina um pedido de troca para a próxima view.
+    ///
+    /// Retorna `None` se este nó já votou por essa view. O voto local é
+    /// contabilizado antes do retorno.
+    pub(crate) async fn request_view_change(&self) -> Result<Option<ViewChange>> {
+        let new_view = {
+            let mut state = self.view.lock().await;
+            let Some(new_view) = state.view.checked_add(1) else {
+                return Ok(None);
+            };
+            if state.voted_for >= new_view {
+                return Ok(None);
+            }
+            state.voted_for = new_view;
+            new_view
+        };
+
+        let vc = self.sign_view_change(new_view).await?;
+        warn!("⏱️ Líder sem resposta; pedindo troca para a view {}", new_view);
+        tracing::info!(target: "consensus", "EVENT:VIEW_CHANGE_VOTE view={} voter={}", new_view, vc.voter);
+
+        self.keep_own_vote(&vc).await;
+        self.record_view_vote(new_view, vc.voter.clone()).await;
+        Ok(Some(vc))
+    }
+
+    /// Assina um voto pela view corrente quando ela foi alcançada sem voto
+    /// deste nó, para que ele também a redivulgue.
+    async fn endorse_current_view(&self) -> Result<()> {
+        let view = {
+            let state = self.view.lock().await;
+            if state.own_vote.as_ref().is_some_and(|own| own.new_view >= state.view) {
+                return Ok(());
+            }
+            state.view
+        };
+        let vc = self.sign_view_change(view).await?;
+        self.keep_own_vote(&vc).await;
+        Ok(())
+    }
+
+    /// Processa um pedido de troca de view vindo da rede.
+    ///
+    /// Retorna `true` se a view avançou (e um novo líder deve ser eleito).
+    pub(crate) async fn handle_view_change(&self, bytes: Vec<u8>) -> Result<bool> {
+        let vc: ViewChange = bincode::deserialize(&bytes)
+            .map_err(|e| AtlasError::Other(format!("decode view change: {e}")))?;
+
+        let ok = self.auth.read().await
+            .verify_with_key(&view_change_signing_bytes(&vc), &vc.signature, &vc.public_key)
+            .map_err(|e| AtlasError::Auth(format!("verify failed: {e}")))?;
+        if !ok {
+            warn!("❌ Assinatura INVÁLIDA no pedido de troca de view de {}", vc.voter);
+            return Err(AtlasError::Auth(format!("assinatura inválida para view change de {}", vc.voter)));
+        }
+        if !key_matches_node(&vc.voter, &vc.public_key) {
+            warn!("❌ Chave do pedido de troca de view não pertence a {}", vc.voter);
+            return Err(AtlasError::Auth(format!("chave não pertence a {}", vc.voter)));
+        }
+
+        tracing::info!(target: "consensus", "EVENT:RECEIVE_VIEW_CHANGE view={} voter={}", vc.new_view, vc.voter);
+        if !self.record_view_vote(vc.new_view, vc.voter).await {
+            return Ok(false);
+        }
+        self.endorse_current_view().await?;
+        Ok(true)
+    }
+
+    /// Contabiliza um voto e avança a view ao atingir quórum (mais de 2/3).
+    ///
+    /// Só contam votos de peers ativos e do próprio nó. A view salta para a
+    /// maior view apoiada pelo quórum (ver `quorum_view`), o que permite a
+    /// um nó reiniciado alcançar o cluster a partir dos votos redivulgados.
+    async fn record_view_vote(&self, new_view: u64, voter: NodeId) -> bool {
+        let active = self.peer_manager.read().await.get_active_peers();
+        let local = self.local_node.read().await.id.clone();
+        if voter != local && !active.contains(&voter) {
+            warn!("Voto de troca de view ignorado: {} não é um peer ativo", voter);
+            return false;
+        }
+        let members = active.len() + 1;
+        let quorum = members * 2 / 3 + 1;
+
+        let now = self.clock.monotonic();
+        let mut state = self.view.lock().await;
+        if new_view <= state.view {
+            return false;
+        }
+        let requested = state.requested.entry(voter).or_insert(new_view);
+        *requested = (*requested).max(new_view);
+
+        let members_requested = state.requested
+            .iter()
+            .filter(|(id, _)| **id == local || active.contains(*id))
+            .map(|(_, view)| *view);
+        let Some(target) = quorum_view(members_requested, quorum).filter(|target| *target > state.view) else {
+            return false;
+        };
+
+        info!("🔄 Quórum de troca de view atingido ({} membros): view {} -> {}", members, state.view, target);
+        tracing::info!(target: "consensus", "EVENT:NEW_VIEW view={}", target);
+        state.view = target;
+        state.voted_for = state.voted_for.max(target);
+        state.requested.retain(|_, view| *view > target);
+        state.leader_seen = now;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atlas_sdk::auth::Authenticator;
+
+    use atlas_sdk::env::{
+        consensus::{certificate::QuorumCertificate, types::{ConsensusResult, Vote}},
+        proposal::Proposal,
+        vote_data::VoteData,
+    };
+
+    use crate::cluster::core::tests::{cluster, cluster_as, identity};
+
+    fn candidates(n: usize) -> HashMap<NodeId, u64> {
+        (0..n).map(|i| (NodeId(format!("node-{}", i)), BASE_ELECTION_WEIGHT)).collect()
+    }
+
+    #[test]
+    fn test_select_leader_is_deterministic_and_rotates() {
+        let set = candidates(4);
+        let block = [7u8; 32];
+        assert_eq!(select_leader(&set, &block, 7), select_leader(&set.clone(), &block, 7));
+        assert_eq!(select_leader(&HashMap::new(), &block, 0), None);
+
+        let leaders: HashSet<NodeId> = (0..32).filter_map(|v| select_leader(&set, &block, v)).collect();
+        assert!(leaders.len() > 1, "advancing the view must hand leadership to other nodes");
+
+        let leaders: HashSet<NodeId> = (0..32u8).filter_map(|b| select_leader(&set, &[b; 32], 0)).collect();
+        assert!(leaders.len() > 1, "the last block hash must feed the seed");
+    }
+
+    #[test]
+    fn test_silent_candidates_stop_winning() {
+        assert_eq!(election_weight(MISSED_COMMITS_GRACE), BASE_ELECTION_WEIGHT);
+        assert_eq!(election_weight(MISSED_COMMITS_GRACE + 1), BASE_ELECTION_WEIGHT / 2);
+        assert_eq!(election_weight(100), 0);
+
+        let (a, b) = (NodeId("node-a".into()), NodeId("node-b".into()));
+        let recent: Vec<HashSet<NodeId>> = (0..PARTICIPATION_WINDOW).map(|_| HashSet::from([a.clone()])).collect();
+        let weights = election_weights([a.clone(), b.clone()], &recent);
+        assert_eq!(weights[&a], BASE_ELECTION_WEIGHT);
+        assert_eq!(weights[&b], election_weight(PARTICIPATION_WINDOW as u32));
+
+        let mut set = candidates(4);
+        let silent = NodeId("node-0".into());
+        set.insert(silent.clone(), 0);
+        assert!((0..64).all(|v| select_leader(&set, &[0u8; 32], v) != Some(silent.clone())));
+    }
+
+    async fn send_view_change(cluster: &Cluster, new_view: u64, voter: &NodeId, auth: &dyn Authenticator) -> Result<bool> {
+        let mut vc = ViewChange { new_view, voter: voter.clone(), signature: [0u8; 64], public_key: auth.public_key() };
+        vc.signature = auth.sign(view_change_signing_bytes(&vc)).unwrap().try_into().unwrap();
+        cluster.handle_view_change(vc.bytes()).await
+    }
+
+    #[tokio::test]
+    async fn test_view_change_counts_only_active_peers() {
+        let (peer, peer_auth) = identity();
+        let node = cluster([peer.clone()]);
+
+        // Quórum de 2 membros: dois votos. Votos de fora do conjunto ativo não contam.
+        for _ in 0..3 {
+            let (outsider, auth) = identity();
+            assert!(!send_view_change(&node, 1, &outsider, &auth).await.unwrap());
+        }
+        assert!(node.request_view_change().await.unwrap().is_some());
+        assert!(send_view_change(&node, 1, &peer, &peer_auth).await.unwrap());
+        assert_eq!(node.current_view().await, 1);
+    }
+
+    #[test]
+    fn test_quorum_view_takes_the_highest_view_backed_by_a_quorum() {
+        assert_eq!(quorum_view([5, 5, 5], 3), Some(5));
+        assert_eq!(quorum_view([4, 6, 5], 3), Some(4));
+        assert_eq!(quorum_view([9, 2, 7, 1], 2), Some(7));
+        assert_eq!(quorum_view([u64::MAX], 2), None);
+        assert_eq!(quorum_view([3], 0), None);
+    }
+
+    #[tokio::test]
+    async fn test_view_change_rejects_foreign_keys_and_lone_jumps() {
+        let (peer, peer_auth) = identity();
+        let (other, _) = identity();
+        let node = cluster([peer.clone(), other]);
+
+        // Assinado com outra chave em nome de um peer ativo.
+        let (_, forger) = identity();
+        assert!(send_view_change(&node, 1, &peer, &forger).await.is_err());
+
+        // Um único voto por uma view distante não move a view.
+        assert!(!send_view_change(&node, u64::MAX, &peer, &peer_auth).await.unwrap());
+        assert_eq!(node.current_view().await, 0);
+
+        node.view.lock().await.view = u64::MAX;
+        assert!(node.request_view_change().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lagging_node_catches_up_to_a_quorum_backed_view() {
+        let peers: Vec<_> = (0..3).map(|_| identity()).collect();
+        let node = cluster(peers.iter().map(|(id, _)| id.clone()));
+        assert!(node.view_vote().await.is_none());
+
+        // 4 membros, quórum 3: o nó reiniciado recebe os votos redivulgados
+        // de views diferentes e salta para a maior apoiada pelo quórum.
+        assert!(!send_view_change(&node, 6, &peers[0].0, &peers[0].1).await.unwrap());
+        assert!(!send_view_change(&node, 5, &peers[1].0, &peers[1].1).await.unwrap());
+        assert!(send_view_change(&node, 4, &peers[2].0, &peers[2].1).await.unwrap());
+        assert_eq!(node.current_view().await, 4);
+
+        // Passa a redivulgar um voto próprio pela view alcançada.
+        let own = node.view_vote().await.unwrap();
+        assert_eq!(own.new_view, 4);
+        assert_eq!(own.voter, node.local_node.read().await.id);
+
+        // Os votos acima da view alcançada continuam valendo.
+        assert!(send_view_change(&node, 5, &peers[2].0, &peers[2].1).await.unwrap());
+        assert_eq!(node.current_view().await, 5);
+        assert!(!send_view_change(&node, 5, &peers[2].0, &peers[2].1).await.unwrap());
+    }
+
+    fn commit_signed_by(cluster: &Cluster, id: &str, signers: &[&NodeId]) {
+        let mut storage = cluster.local_env.storage.try_write().unwrap();
+        storage.log_proposal(Proposal {
+            id: id.into(),
+            proposer: signers[0].clone(),
+            content: "{}".into(),
+            parent: None,
+            signature: [0u8; 64],
+            public_key: Vec::new(),
+            request_id: None,
+        });
+        storage.log_result(id, ConsensusResult { proposal_id: id.into(), approved: true, votes_received: signers.len() });
+        storage.log_certificate(QuorumCertificate::new(id, signers.iter().map(|voter| VoteData {
+            proposal_id: id.into(),
+            vote: Vote::Yes,
+            voter: (*voter).clone(),
+            signature: [0u8; 64],
+            public_key: Vec::new(),
+        })));
+    }
+
+    #[tokio::test]
+    async fn test_nodes_with_the_same_ledger_elect_the_same_leader() {
+        let (a, b, c) = (identity(), identity(), identity());
+        let (id_a, id_b, id_c) = (a.0.clone(), b.0.clone(), c.0.clone());
+        let node_a = cluster_as(a, [id_b.clone(), id_c.clone()]);
+        let node_b = cluster_as(b, [id_a.clone(), id_c.clone()]);
+
+        // `c` parou de assinar; cada nó o viu pela última vez em momentos diferentes.
+        for node in [&node_a, &node_b] {
+            commit_signed_by(node, "p0", &[&id_a, &id_b, &id_c]);
+            for i in 1..=PARTICIPATION_WINDOW {
+                commit_signed_by(node, &format!("p{}", i), &[&id_a, &id_b]);
+            }
+        }
+
+        for view in 0..16 {
+            for node in [&node_a, &node_b] {
+                node.view.lock().await.view = view;
+                node.elect_leader().await;
+            }
+            let leader = node_a.current_leader.read().await.clone();
+            assert!(leader.is_some());
+            assert_eq!(leader, *node_b.current_leader.read().await, "view {}", view);
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use crate::{
     cluster::core::Cluster,
-    env::vote_data::{VoteData, vote_signing_bytes},
+    env::{proposal::Proposal, storage::VoteRecord, vote_data::{VoteData, vote_signing_bytes}},
     error::{AtlasError, Result},
 };
 
 use atlas_sdk::{
-    env::consensus::types::Vote,
+    env::{consensus::types::Vote, misbehavior::MisbehaviorReport},
 };
 use tracing::{info, warn};
 
@@ -20,50 +20,66 @@ impl Cluster {
         let mut out = Vec::new();
 
         for (_, proposal) in proposal_pool {
-            // 1) decide o voto
-            // Use standardized signing bytes for proposal verification
-            let sign_bytes = crate::env::proposal::signing_bytes(&proposal);
-            let is_valid = self.auth.read().await
-                .verify_with_key(sign_bytes, &proposal.signature, &proposal.public_key)
-                .map_err(|e| AtlasError::Auth(format!("Verification failed: {}", e)))?;
-
-            let vote = match is_valid {
-                true => Vote::Yes,
-                false => Vote::No,
-            };
-
-            // 2) monta VoteData (sem assinatura)
-            let mut vote_data = VoteData {
-                proposal_id: proposal.id.clone(),
-                vote,
-                voter: self.local_node.read().await.id.clone(),
-                signature: [0u8; 64],
-                public_key: self.auth.read().await.public_key(),
-            };
-
-            // 3) assina canonicamente
-            let msg = vote_signing_bytes(&vote_data);
-            let sig_vec = self.auth.read().await.sign(msg)
-                .map_err(|e| AtlasError::Auth(format!("Signing failed: {}", e)))?;
-                
-            let sig_arr: [u8; 64] = sig_vec
-                .try_into()
-                .map_err(|_| AtlasError::Auth("assinatura inválida: tamanho incorreto".to_string()))?;
-            vote_data.signature = sig_arr;
-
-            info!("📝 Publicando voto: {:?}", vote_data);
-            tracing::info!(target: "consensus", "EVENT:VOTE proposal_id={} voter={} vote={:?}", vote_data.proposal_id, vote_data.voter, vote_data.vote);
-
-            // 4) publica no tópico atlas/vote/v1
-            out.push(vote_data);
+            out.push(self.vote_on(&proposal).await?);
         }
 
         Ok(out)
     }
+
+    /// Verifica a proposta e devolve o voto assinado deste nó.
+    #[tracing::instrument(name = "vote", skip_all, fields(proposal_id = %proposal.id, vote = tracing::field::Empty))]
+    async fn vote_on(&self, proposal: &Proposal) -> Result<VoteData> {
+        // 1) decide o voto
+        // Use standardized signing bytes for proposal verification
+        let sign_bytes = crate::env::proposal::signing_bytes(proposal);
+        let is_valid = self.auth.read().await
+            .verify_with_key(&sign_bytes, &proposal.signature, &proposal.public_key)
+            .map_err(|e| AtlasError::Auth(format!("Verification failed: {}", e)))?;
+
+        let vote = match is_valid {
+            true => Vote::Yes,
+            false => Vote::No,
+        };
+        tracing::Span::current().record("vote", tracing::field::debug(&vote));
+
+        // 2) monta VoteData (sem assinatura)
+        let mut vote_data = VoteData {
+            proposal_id: proposal.id.clone(),
+            vote,
+            voter: self.local_node.read().await.id.clone(),
+            signature: [0u8; 64],
+            public_key: self.auth.read().await.public_key(),
+        };
+
+        // 3) assina canonicamente
+        let msg = vote_signing_bytes(&vote_data);
+        let sig_vec = self.auth.read().await.sign(msg)
+            .map_err(|e| AtlasError::Auth(format!("Signing failed: {}", e)))?;
+            
+        let sig_arr: [u8; 64] = sig_vec
+            .try_into()
+            .map_err(|_| AtlasError::Auth("assinatura inválida: tamanho incorreto".to_string()))?;
+        vote_data.signature = sig_arr;
+
+        info!("📝 Publicando voto: {:?}", vote_data);
+        tracing::info!(target: "consensus", "EVENT:VOTE proposal_id={} voter={} vote={:?}", vote_data.proposal_id, vote_data.voter, vote_data.vote);
+
+        // 4) publica no tópico atlas/vote/v1
+        self.record_vote(&vote_data, true).await;
+        Ok(vote_data)
+    }
         
-    pub(crate) async fn handle_vote(&self, bytes: Vec<u8>) -> Result<()> {
+    /// Processa um voto recebido.
+    ///
+    /// Devolve um relatório assinado quando o voto prova que o autor votou
+    /// em duplicidade, para ser divulgado aos peers.
+    #[tracing::instrument(name = "receive_vote", skip_all, fields(proposal_id = tracing::field::Empty, voter = tracing::field::Empty))]
+    pub(crate) async fn handle_vote(&self, bytes: Vec<u8>) -> Result<Option<MisbehaviorReport>> {
         let vote_data: VoteData = bincode::deserialize(&bytes)
             .map_err(|e| AtlasError::Other(format!("decode vote: {e}")))?;
+        let span = tracing::Span::current();
+        span.record("proposal_id", vote_data.proposal_id.as_str());
+        span.record("voter", tracing::field::display(&vote_data.voter));
 
         let signature_array: [u8; 64] = vote_data.signature
             .as_slice()
@@ -74,11 +90,11 @@ impl Cluster {
 
         // Use standardized signing bytes for vote verification
         let sign_bytes = vote_signing_bytes(&vote_data);
-        let is_valid = match auth.verify_with_key(sign_bytes, &signature_array, &vote_data.public_key) {
+        let is_valid = match auth.verify_with_key(&sign_bytes, &signature_array, &vote_data.public_key) {
             Ok(valid) => valid,
             Err(e) => {
                 warn!("Erro ao verificar assinatura do voto: {}", e);
-                return Ok(());
+                return Ok(None);
             }
         };
         drop(auth);
@@ -91,12 +107,91 @@ impl Cluster {
         tracing::info!(target: "consensus", "EVENT:RECEIVE_VOTE proposal_id={} voter={} vote={:?}", vote_data.proposal_id, vote_data.voter, vote_data.vote);
 
 
-        if is_valid {
-            self.local_env.engine.lock().await.receive_vote(vote_data.clone()).await;
-    
-            Ok(())
-        } else {
-            Ok(())
+        if !is_valid {
+            return Ok(None);
+        }
+
+        // votos atrasados para propostas já finalizadas não voltam ao registro
+        let finalized = self.local_env.storage.read().await.results
+            .get(&vote_data.proposal_id)
+            .is_some_and(|r| r.approved);
+        if finalized {
+            return Ok(None);
+        }
+
+        let accepted = {
+            let auth = self.auth.read().await;
+            self.local_env.engine.lock().await.receive_vote(vote_data.clone(), &*auth).await
+        };
+        // só votos aceitos pelo motor (votante ativo, chave do votante) vão para o log
+        let Ok(evidence) = accepted else {
+            return Ok(None);
+        };
+        self.record_vote(&vote_data, false).await;
+
+        match evidence {
+            Some(evidence) => self.report_misbehavior(evidence).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Registra um voto assinado no log de votos do storage, com o horário de recebimento.
+    ///
+    /// A métrica de votos só conta registros novos: reassinar uma proposta
+    /// já votada não é um voto a mais.
+    async fn record_vote(&self, vote_data: &VoteData, local: bool) {
+        let received_at_ms = self.clock.now_ms();
+        let inserted = self.local_env.storage.write().await.log_vote_record(
+            &vote_data.proposal_id,
+            VoteRecord { voter: vote_data.voter.clone(), vote: vote_data.vote.clone(), received_at_ms },
+        );
+        if inserted {
+            self.metrics.record_vote(&vote_data.vote, local);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atlas_sdk::{auth::{ed25519::Ed25519Authenticator, Authenticator}, utils::NodeId};
+
+    use crate::cluster::core::tests::{cluster, identity};
+    use crate::metrics::VoteLabels;
+
+    fn vote_bytes(voter: &NodeId, auth: &Ed25519Authenticator) -> Vec<u8> {
+        let mut data = VoteData {
+            proposal_id: "p1".into(),
+            vote: Vote::Yes,
+            voter: voter.clone(),
+            signature: [0u8; 64],
+            public_key: auth.public_key(),
+        };
+        data.signature = auth.sign(vote_signing_bytes(&data)).unwrap().try_into().unwrap();
+        bincode::serialize(&data).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_only_accepted_votes_are_recorded() {
+        let (voter, voter_auth) = identity();
+        let node = cluster([voter.clone()]);
+
+        let (outsider, outsider_auth) = identity();
+        node.handle_vote(vote_bytes(&outsider, &outsider_auth)).await.unwrap();
+        assert!(node.local_env.storage.read().await.vote_log.is_empty());
+
+        // Repetir o mesmo voto não faz o log crescer.
+        for _ in 0..3 {
+            node.handle_vote(vote_bytes(&voter, &voter_auth)).await.unwrap();
         }
+        let storage = node.local_env.storage.read().await;
+        let log = &storage.vote_log["p1"];
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].voter, voter);
+        assert_eq!(storage.validator_stats(&voter).votes_cast, 1);
+        assert_eq!(storage.validator_stats(&outsider).votes_cast, 0);
+
+        let counted = node.metrics.votes.get_or_create(&VoteLabels { vote: "yes", source: "peer" }).get();
+        assert_eq!(counted, 1);
     }
 }
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use tracing::{info, warn};
+
+use crate::cluster::core::Cluster;
+
+/// Tempo sem commit, com propostas pendentes e peers ativos, até o nó se considerar travado.
+pub const STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Quantos `timeout`s uma proposta conta como pendente. Depois disso ela já
+/// motivou algumas trocas de view sem chegar a quórum (foi rejeitada, nunca
+/// terá votos ou é lixo recebido por gossip) e deixa de manter o alarme ligado.
+const PENDING_TIMEOUTS: u32 = 3;
+
+/// Estado do watchdog de progresso do consenso.
+#[derive(Debug, Default)]
+pub struct Watchdog {
+    /// Último commit, ou último instante em que não havia nada a commitar
+    /// (`Clock::monotonic`).
+    last_progress: Duration,
+    /// Quando cada proposta do pool foi vista pela primeira vez pelo watchdog.
+    first_seen: HashMap<String, Duration>,
+}
+
+impl Cluster {
+    /// Registra progresso do consenso (um commit) e encerra um incidente aberto.
+    pub(crate) async fn note_progress(&self) {
+        self.watchdog.lock().await.last_progress = self.clock.monotonic();
+        if self.stalled.swap(false, Ordering::SeqCst) {
+            info!("🐕 Consenso voltou a progredir");
+            tracing::info!(target: "consensus", "EVENT:STALL_RECOVERED");
+        }
+    }
+
+    /// Verdadeiro quando há propostas pendentes e peers ativos, mas nenhum
+    /// commit há mais de `timeout`.
+    ///
+    /// Só contam as propostas no pool há no máximo `PENDING_TIMEOUTS`
+    /// vezes `timeout`: o pool só encolhe em commits, então num cluster
+    /// ocioso uma proposta abandonada ficaria lá para sempre.
+    ///
+    /// Um nó sem propostas pendentes está ocioso, não travado: o relógio é
+    /// reiniciado. Após disparar, o relógio também recomeça, para a próxima
+    /// ação de recuperação esperar outro `timeout`.
+    pub(crate) async fn check_stall(&self, timeout: Duration) -> bool {
+        let in_pool: Vec<String> = self.local_env.engine.lock().await.pool.all().keys().cloned().collect();
+        let peers = self.peer_manager.read().await.get_active_peers().len();
+
+        let now = self.clock.monotonic();
+        let mut watchdog = self.watchdog.lock().await;
+        watchdog.first_seen.retain(|id, _| in_pool.contains(id));
+        for id in in_pool {
+            watchdog.first_seen.entry(id).or_insert(now);
+        }
+        let pending = watchdog.first_seen
+            .values()
+            .filter(|seen| now.saturating_sub(**seen) <= timeout * PENDING_TIMEOUTS)
+            .count();
+
+        if pending == 0 || peers == 0 {
+            watchdog.last_progress = now;
+            return false;
+        }
+
+        let idle = now.saturating_sub(watchdog.last_progress);
+        if idle <= timeout {
+            return false;
+        }
+        watchdog.last_progress = now;
+        self.stalled.store(true, Ordering::SeqCst);
+
+        warn!("🐕 Consenso parado há {:?} com {} propostas pendentes e {} peers; forçando troca de view", idle, pending, peers);
+        tracing::warn!(target: "consensus", "EVENT:STALL idle_secs={} pending={} peers={}", idle.as_secs(), pending, peers);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::UNIX_EPOCH;
+
+    use atlas_sdk::utils::NodeId;
+
+    use crate::cluster::core::tests::{cluster, identity};
+    use crate::env::{clock::ManualClock, proposal::Proposal};
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn stalled_cluster() -> (Cluster, Arc<ManualClock>) {
+        let clock = Arc::new(ManualClock::new(UNIX_EPOCH));
+        let mut cluster = cluster([identity().0]);
+        cluster.clock = clock.clone();
+        (cluster, clock)
+    }
+
+    async fn add_pending(cluster: &Cluster, id: &str) {
+        cluster.local_env.engine.lock().await.add_proposal(Proposal {
+            id: id.to_string(),
+            proposer: NodeId("n1".to_string()),
+            content: "{}".to_string(),
+            parent: None,
+            signature: [0u8; 64],
+            public_key: vec![],
+            request_id: None,
+        });
+    }
+
+    #[tokio::test]
+    async fn test_pending_proposal_without_commits_is_a_stall() {
+        let (cluster, clock) = stalled_cluster();
+        add_pending(&cluster, "p1").await;
+
+        assert!(!cluster.check_stall(TIMEOUT).await);
+        clock.advance(TIMEOUT + Duration::from_secs(1));
+        assert!(cluster.check_stall(TIMEOUT).await);
+        assert!(cluster.stalled.load(Ordering::SeqCst));
+
+        // o relógio recomeça após disparar
+        assert!(!cluster.check_stall(TIMEOUT).await);
+        cluster.note_progress().await;
+        assert!(!cluster.stalled.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_idle_cluster_with_stale_proposals_stops_forcing_view_changes() {
+        let (cluster, clock) = stalled_cluster();
+        add_pending(&cluster, "abandoned").await;
+        assert!(!cluster.check_stall(TIMEOUT).await);
+
+        let mut fired = 0;
+        for _ in 0..20 {
+            clock.advance(TIMEOUT + Duration::from_secs(1));
+            if cluster.check_stall(TIMEOUT).await {
+                fired += 1;
+            }
+        }
+        assert!(fired > 0 && fired < PENDING_TIMEOUTS as usize, "disparou {} vezes", fired);
+
+        // uma proposta nova volta a contar
+        add_pending(&cluster, "fresh").await;
+        assert!(!cluster.check_stall(TIMEOUT).await);
+        clock.advance(TIMEOUT + Duration::from_secs(1));
+        assert!(cluster.check_stall(TIMEOUT).await);
+    }
+}
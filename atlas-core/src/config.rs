@@ -1,4 +1,4 @@
-use std::{fs, io, sync::Arc};
+use std::{fs, io, net::SocketAddr, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 use tokio::sync::{Mutex, RwLock};
@@ -13,11 +13,12 @@ use atlas_sdk::{
 };
 
 use crate::{
-    cluster::core::Cluster,
-    env::runtime::AtlasEnv, 
+    cluster::{core::Cluster, state_check::StateCheckMode},
+    env::{retention::RetentionPolicy, runtime::AtlasEnv}, 
     peer_manager::PeerManager,
     env::storage::Storage,
     env::consensus::evaluator::QuorumPolicy,
+    version::UpgradePlan,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +30,28 @@ pub struct Config {
     pub graph: Graph,
     pub storage: Storage,
     pub peer_manager: PeerManager,
+    /// Reação a divergências de state root (`off`, `warn`, `halt`).
+    #[serde(default)]
+    pub state_check: StateCheckMode,
+    /// Idade máxima de logs e dumps de divergência no disco.
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// Chaves públicas ed25519 (hex) autorizadas a chamar RPCs de admin.
+    /// Vazio desabilita os RPCs de admin.
+    #[serde(default)]
+    pub admin_keys: Vec<String>,
+    /// Upgrade de protocolo agendado (versão e altura de ativação).
+    #[serde(default)]
+    pub upgrade: Option<UpgradePlan>,
+    /// Endereço do servidor JSON-RPC (sem TLS; só no líder; escritas só em loopback). Ausente desabilita.
+    #[serde(default)]
+    pub jsonrpc_addr: Option<SocketAddr>,
+    /// Endereço do `/metrics` (Prometheus). Ausente desabilita.
+    #[serde(default)]
+    pub metrics_addr: Option<SocketAddr>,
+    /// Confirmações (alturas acima) até um commit ser servido como finalizado.
+    #[serde(default)]
+    pub finality_depth: u64,
 }
 
 impl Config {
@@ -44,7 +67,11 @@ impl Config {
             self.quorum_policy,
         );
 
-        for proposal in &self.storage.proposals {
+        // propostas já finalizadas ficam só no storage
+        let pending = self.storage.proposals
+            .iter()
+            .filter(|p| !self.storage.results.get(&p.id).is_some_and(|r| r.approved));
+        for proposal in pending {
             engine.pool.add(proposal.clone());
             engine.registry.register_proposal(&proposal.id);
         }
@@ -59,12 +86,20 @@ impl Config {
             peer_manager: Arc::clone(&peer_manager),
         };
 
-        Cluster::new(env, self.node_id, auth)
+        let mut cluster = Cluster::new(env, self.node_id, auth);
+        cluster.state_check = self.state_check;
+        cluster.retention = self.retention;
+        cluster.admin_keys = self.admin_keys;
+        cluster.upgrade = self.upgrade;
+        cluster.jsonrpc_addr = self.jsonrpc_addr;
+        cluster.metrics_addr = self.metrics_addr;
+        cluster.finality_depth = self.finality_depth;
+        cluster
     }
 
     pub fn save_to_file<P: AsRef<std::path::Path>>(&self, path: P) -> io::Result<()> {
         let json = serde_json::to_string_pretty(self)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            .map_err(io::Error::other)?;
         fs::write(path, json)
     }
 
@@ -70,14 +70,14 @@ impl EnvConfig {
 
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
         let json = serde_json::to_string_pretty(self)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            .map_err(io::Error::other)?;
         fs::write(path, json)
     }
 
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
         let json = fs::read_to_string(path)?;
         let config = serde_json::from_str(&json)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            .map_err(io::Error::other)?;
         Ok(config)
     }
 
@@ -6,8 +6,9 @@ use tokio::sync::{RwLock};
 use tracing::{info, warn};
 
 use atlas_sdk::{
+    auth::Authenticator,
     utils::NodeId,
-    env::consensus::types::{Vote, ConsensusResult},
+    env::consensus::{evidence::VoteEquivocation, types::ConsensusResult},
 };
 
 use crate::{
@@ -19,6 +20,7 @@ use crate::{
 
 use super::{
     evaluator::{ConsensusEvaluator, QuorumPolicy},
+    jail::Jail,
     pool::ProposalPool,
     registry::VoteRegistry,
 };
@@ -30,6 +32,7 @@ pub struct ConsensusEngine {
     pub pool: ProposalPool,
     pub registry: VoteRegistry,
     pub evaluator: ConsensusEvaluator,
+    pub jail: Jail,
 }
 
 impl ConsensusEngine {
@@ -39,6 +42,7 @@ impl ConsensusEngine {
             pool: ProposalPool::new(),
             registry: VoteRegistry::new(),
             evaluator: ConsensusEvaluator::new(policy),
+            jail: Jail::default(),
         }
     }
 
@@ -49,20 +53,70 @@ impl ConsensusEngine {
     }
     
     /// Registra voto recebido de um peer.
-    pub(crate) async fn receive_vote(&mut self, vote_msg: VoteData) {
+    ///
+    /// Devolve a prova de duplicidade quando o voto faz o autor ser preso, e
+    /// `Err` com o motivo quando o voto é recusado.
+    pub(crate) async fn receive_vote(&mut self, vote_msg: VoteData, auth: &dyn Authenticator) -> Result<Option<VoteEquivocation>, String> {
         let voter = vote_msg.voter.clone();
         if !self.get_active_nodes().await.contains(&voter) {
             warn!("⚠️ Ignorado voto de nó inativo: [{}]", vote_msg.voter.clone());
-            return;
+            return Err(format!("{} não é um votante ativo", voter));
         }
 
-        match Vote::try_from(vote_msg.vote.clone()) {
-            Ok(vote) => {
-                self.registry.register_vote(&vote_msg.proposal_id, voter.clone(), vote.clone());
-                info!("📥 [{}] votou {:?} na proposta [{}]", voter, vote, vote_msg.proposal_id);
+        let vote = vote_msg.vote.clone();
+        let proposal_id = vote_msg.proposal_id.clone();
+        match self.registry.register_signed_vote(vote_msg, auth) {
+            Ok(Some(evidence)) => {
+                self.jail_equivocator(&evidence);
+                return Ok(Some(evidence));
             }
-            Err(_) => warn!("⚠️ Voto inválido ignorado: {}", vote_msg.vote.to_string()),
+            Ok(None) => {}
+            Err(e) => {
+                warn!("⚠️ Ignorado voto de [{}] na proposta [{}]: {}", voter, proposal_id, e);
+                return Err(e);
+            }
+        }
+        info!("📥 [{}] votou {:?} na proposta [{}]", voter, vote, proposal_id);
+        Ok(None)
+    }
+
+    /// Prende um nó que votou em duplicidade e descarta os votos dele.
+    fn jail_equivocator(&mut self, evidence: &VoteEquivocation) {
+        let offender = evidence.offender().clone();
+        let until = self.jail.jail(offender.clone(), self.evaluator.policy.jail_commits);
+        self.registry.remove_voter(&offender);
+
+        warn!(
+            "⛓️ [{}] votou {:?} e {:?} na proposta [{}]; preso por {} commits",
+            offender, evidence.first.vote, evidence.second.vote, evidence.first.proposal_id, self.evaluator.policy.jail_commits
+        );
+        tracing::warn!(target: "consensus", "EVENT:JAIL node={} proposal_id={} until={}", offender, evidence.first.proposal_id, until);
+    }
+
+    /// Registra um commit, soltando os nós cuja pena terminou.
+    pub(crate) fn on_commit(&mut self) {
+        for node in self.jail.on_commit() {
+            info!("🔓 [{}] cumpriu a pena e volta a votar", node);
+            tracing::info!(target: "consensus", "EVENT:UNJAIL node={}", node);
+        }
+    }
+
+    /// Tira uma proposta finalizada do pool e do registro de votos, e
+    /// descarta as propostas abandonadas há mais de `pool_window` commits.
+    ///
+    /// Devolve a proposta finalizada, se ainda estava no pool, e os IDs
+    /// das propostas descartadas.
+    pub(crate) fn finalize_proposal(&mut self, proposal_id: &str) -> (Option<Proposal>, Vec<String>) {
+        let proposal = self.pool.finalize(proposal_id);
+        self.registry.remove_proposal(proposal_id);
+
+        let pruned = self.pool.prune_stale(self.evaluator.policy.pool_window);
+        for id in &pruned {
+            self.registry.remove_proposal(id);
+            info!("🧹 Proposta [{}] descartada do pool sem quórum", id);
+            tracing::info!(target: "consensus", "EVENT:PRUNE id={}", id);
         }
+        (proposal, pruned)
     }
 
     /// Avalia todas as propostas e retorna os resultados.
@@ -81,11 +135,59 @@ impl ConsensusEngine {
         &self.pool
     }
 
-    /// Expõe os nós ativos (com leitura protegida).
+    /// Expõe os nós ativos (com leitura protegida), sem os nós presos.
     async fn get_active_nodes(&self) -> HashSet<NodeId> {
-        self.peer_manager
+        let mut active = self.peer_manager
             .read()
             .await
-            .get_active_peers()
+            .get_active_peers();
+        active.retain(|id| !self.jail.is_jailed(id));
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atlas_sdk::{
+        auth::ed25519::Ed25519Authenticator,
+        env::{consensus::types::Vote, vote_data::vote_signing_bytes},
+    };
+
+    use crate::cluster::core::tests::identity;
+
+    fn signed_vote(voter: &NodeId, auth: &Ed25519Authenticator, vote: Vote) -> VoteData {
+        let mut data = VoteData {
+            proposal_id: "p1".into(),
+            vote,
+            voter: voter.clone(),
+            signature: [0u8; 64],
+            public_key: auth.public_key(),
+        };
+        data.signature = auth.sign(vote_signing_bytes(&data)).unwrap().try_into().unwrap();
+        data
+    }
+
+    fn engine(voters: &[&NodeId]) -> ConsensusEngine {
+        let mut peers = PeerManager::new(8, 8);
+        peers.active_peers.extend(voters.iter().map(|v| (*v).clone()));
+        ConsensusEngine::new(Arc::new(RwLock::new(peers)), QuorumPolicy::default())
+    }
+
+    #[tokio::test]
+    async fn test_forged_conflicting_vote_does_not_jail_the_voter() {
+        let (honest, honest_auth) = identity();
+        let (_, forger) = identity();
+        let mut engine = engine(&[&honest]);
+
+        assert!(matches!(engine.receive_vote(signed_vote(&honest, &honest_auth, Vote::Yes), &honest_auth).await, Ok(None)));
+        let forged = signed_vote(&honest, &forger, Vote::No);
+        assert!(engine.receive_vote(forged, &honest_auth).await.is_err());
+        assert!(!engine.jail.is_jailed(&honest));
+        assert_eq!(engine.registry.count_yes("p1"), 1);
+
+        let genuine = signed_vote(&honest, &honest_auth, Vote::No);
+        assert!(matches!(engine.receive_vote(genuine, &honest_auth).await, Ok(Some(_))));
+        assert!(engine.jail.is_jailed(&honest));
     }
 }
@@ -16,11 +16,26 @@ use serde::{Serialize, Deserialize};
 pub struct QuorumPolicy {
     pub fraction: f64,
     pub min_voters: usize,
+    /// Commits durante os quais um nó pego votando em duplicidade fica preso.
+    #[serde(default = "default_jail_commits")]
+    pub jail_commits: u64,
+    /// Commits após os quais uma proposta sem quórum é descartada do pool.
+    /// `0` mantém propostas pendentes para sempre.
+    #[serde(default = "default_pool_window")]
+    pub pool_window: u64,
+}
+
+fn default_jail_commits() -> u64 {
+    100
+}
+
+fn default_pool_window() -> u64 {
+    100
 }
 
 impl Default for QuorumPolicy {
     fn default() -> Self {
-        Self { fraction: 0.5, min_voters: 1 }
+        Self { fraction: 0.5, min_voters: 1, jail_commits: default_jail_commits(), pool_window: default_pool_window() }
     }
 }
 
@@ -86,7 +101,7 @@ mod tests {
 
     #[test]
     fn test_quorum_policy_fraction() {
-        let policy = QuorumPolicy { fraction: 0.5, min_voters: 1 };
+        let policy = QuorumPolicy { fraction: 0.5, min_voters: 1, ..Default::default() };
         let evaluator = ConsensusEvaluator::new(policy);
         let mut registry = VoteRegistry::new();
         let active_nodes: HashSet<NodeId> = vec![
@@ -109,7 +124,7 @@ mod tests {
 
     #[test]
     fn test_quorum_policy_min_voters() {
-        let policy = QuorumPolicy { fraction: 0.1, min_voters: 3 }; // fraction gives 0.4 -> 1, but min is 3
+        let policy = QuorumPolicy { fraction: 0.1, min_voters: 3, ..Default::default() }; // fraction gives 0.4 -> 1, but min is 3
         let evaluator = ConsensusEvaluator::new(policy);
         let mut registry = VoteRegistry::new();
         let active_nodes: HashSet<NodeId> = vec![
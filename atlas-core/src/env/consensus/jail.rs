@@ -0,0 +1,71 @@
+use std::collections::{HashMap, HashSet};
+
+use atlas_sdk::utils::NodeId;
+
+/// Nós temporariamente excluídos da eleição de líder e da contagem de quórum.
+///
+/// A duração é medida em commits: um nó preso na altura `h` volta a
+/// participar quando o nó local atinge `h + jail_commits`.
+#[derive(Debug, Clone, Default)]
+pub struct Jail {
+    /// Commits observados desde o início do processo.
+    height: u64,
+    /// Altura em que cada nó preso é solto.
+    jailed: HashMap<NodeId, u64>,
+}
+
+impl Jail {
+    /// Prende `node` por `commits` commits. Uma nova prisão só estende a pena.
+    pub fn jail(&mut self, node: NodeId, commits: u64) -> u64 {
+        let until = self.height.saturating_add(commits);
+        let entry = self.jailed.entry(node).or_insert(until);
+        *entry = (*entry).max(until);
+        *entry
+    }
+
+    /// Retorna `true` se o nó está preso.
+    pub fn is_jailed(&self, node: &NodeId) -> bool {
+        self.jailed.contains_key(node)
+    }
+
+    /// Nós presos no momento.
+    pub fn jailed_nodes(&self) -> HashSet<NodeId> {
+        self.jailed.keys().cloned().collect()
+    }
+
+    /// Avança um commit e devolve os nós cuja pena terminou.
+    pub fn on_commit(&mut self) -> Vec<NodeId> {
+        self.height += 1;
+        let height = self.height;
+        let released: Vec<NodeId> = self
+            .jailed
+            .iter()
+            .filter(|(_, until)| **until <= height)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &released {
+            self.jailed.remove(id);
+        }
+        released
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jail_expires_after_commits() {
+        let mut jail = Jail::default();
+        let n1 = NodeId("n1".into());
+
+        jail.jail(n1.clone(), 2);
+        assert!(jail.is_jailed(&n1));
+        assert!(jail.on_commit().is_empty());
+
+        // Prender de novo com pena menor não encurta a atual.
+        jail.jail(n1.clone(), 0);
+        assert_eq!(jail.on_commit(), vec![n1.clone()]);
+        assert!(!jail.is_jailed(&n1));
+    }
+}
@@ -11,6 +11,7 @@
 
 mod engine;
 pub mod evaluator;
+pub mod jail;
 mod pool;
 mod registry;
 
@@ -6,18 +6,21 @@ use crate::env::proposal::Proposal;
 #[derive(Debug, Default, Clone)]
 pub struct ProposalPool {
     proposals: HashMap<String, Proposal>,
+    /// Altura do pool (em commits) quando cada proposta entrou.
+    added_at: HashMap<String, u64>,
+    /// Commits finalizados desde a criação do pool.
+    height: u64,
 }
 
 impl ProposalPool {
     /// Create a new empty proposal pool.
     pub fn new() -> Self {
-        Self {
-            proposals: HashMap::new(),
-        }
+        Self::default()
     }
 
     /// Add new proposal to the pool.
     pub fn add(&mut self, proposal: Proposal) {
+        self.added_at.entry(proposal.id.clone()).or_insert(self.height);
         if self.proposals.insert(proposal.clone().id, proposal).is_some() {
             eprintln!("⚠️ Proposal com id já existe no pool");
         }
@@ -32,6 +35,39 @@ impl ProposalPool {
     /// Limpa todas as propostas do pool.
     pub fn clear(&mut self) {
         self.proposals.clear();
+        self.added_at.clear();
+    }
+
+    /// Remove uma proposta finalizada e avança a altura do pool.
+    ///
+    /// A cópia permanente fica no storage; o pool guarda apenas o que ainda
+    /// está em votação. Um ID fora do pool não avança a altura.
+    pub fn finalize(&mut self, id: &str) -> Option<Proposal> {
+        let proposal = self.proposals.remove(id)?;
+        self.height += 1;
+        self.added_at.remove(id);
+        Some(proposal)
+    }
+
+    /// Remove as propostas pendentes há mais de `window` commits e devolve
+    /// os IDs removidos. `0` mantém as propostas para sempre.
+    pub fn prune_stale(&mut self, window: u64) -> Vec<String> {
+        if window == 0 {
+            return Vec::new();
+        }
+
+        let height = self.height;
+        let stale: Vec<String> = self.added_at
+            .iter()
+            .filter(|(_, added)| height.saturating_sub(**added) > window)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for id in &stale {
+            self.added_at.remove(id);
+            self.proposals.remove(id);
+        }
+        stale
     }
 
     /// Find propouse by id.
@@ -39,3 +75,43 @@ impl ProposalPool {
         self.proposals.get(id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atlas_sdk::utils::NodeId;
+
+    fn proposal(id: &str) -> Proposal {
+        Proposal {
+            id: id.to_string(),
+            proposer: NodeId("n1".to_string()),
+            content: "{}".to_string(),
+            parent: None,
+            signature: [0u8; 64],
+            public_key: vec![],
+            request_id: None,
+        }
+    }
+
+    #[test]
+    fn test_finalize_and_prune_stale() {
+        let mut pool = ProposalPool::new();
+        pool.add(proposal("old"));
+        pool.add(proposal("done"));
+
+        assert!(pool.finalize("done").is_some());
+        pool.add(proposal("new"));
+        assert!(pool.prune_stale(1).is_empty());
+
+        // um ID que não está no pool não conta como commit
+        assert!(pool.finalize("x").is_none());
+        assert!(pool.prune_stale(1).is_empty());
+
+        pool.add(proposal("next"));
+        pool.finalize("next");
+        assert_eq!(pool.prune_stale(1), vec!["old".to_string()]);
+        assert!(pool.find_by_id("new").is_some());
+        assert_eq!(pool.all().len(), 1);
+        assert!(pool.prune_stale(0).is_empty());
+    }
+}
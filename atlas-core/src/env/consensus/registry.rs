@@ -1,14 +1,19 @@
 use std::collections::HashMap;
 
 use atlas_sdk::{
+    auth::Authenticator,
     utils::NodeId,
-    env::consensus::types::Vote,
+    env::{consensus::{evidence::VoteEquivocation, types::Vote}, vote_data::VoteData},
 };
 
+use crate::network::key_manager::key_matches_node;
+
 /// Armazena os votos de cada nó para cada proposta.
 #[derive(Debug, Default, Clone)]
 pub struct VoteRegistry {
     votes: HashMap<String, HashMap<NodeId, Vote>>,
+    /// Votos assinados recebidos, usados para montar certificados de quórum.
+    signed: HashMap<String, HashMap<NodeId, VoteData>>,
 }
 
 impl VoteRegistry {
@@ -16,6 +21,7 @@ impl VoteRegistry {
     pub fn new() -> Self {
         Self {
             votes: HashMap::new(),
+            signed: HashMap::new(),
         }
     }
 
@@ -32,6 +38,54 @@ impl VoteRegistry {
             .insert(node, vote);
     }
 
+    /// Registra um voto assinado, mantendo a assinatura para o certificado.
+    ///
+    /// Votos cuja chave não é a do `voter` são recusados. Se o nó já tinha
+    /// votado diferente na mesma proposta, o primeiro voto é mantido e a
+    /// prova da duplicidade é devolvida, desde que ambos os votos sejam
+    /// válidos sob a chave do nó.
+    pub fn register_signed_vote(&mut self, vote: VoteData, auth: &dyn Authenticator) -> Result<Option<VoteEquivocation>, String> {
+        if !key_matches_node(&vote.voter, &vote.public_key) {
+            return Err(format!("chave do voto não pertence a {}", vote.voter));
+        }
+
+        let signed = self.signed.entry(vote.proposal_id.clone()).or_default();
+        if let Some(first) = signed.get(&vote.voter) {
+            if first.vote != vote.vote {
+                let evidence = VoteEquivocation { first: first.clone(), second: vote };
+                evidence.verify(auth)?;
+                return Ok(Some(evidence));
+            }
+        }
+
+        self.register_vote(&vote.proposal_id, vote.voter.clone(), vote.vote.clone());
+        self.signed
+            .entry(vote.proposal_id.clone())
+            .or_default()
+            .insert(vote.voter.clone(), vote);
+        Ok(None)
+    }
+
+    /// Descarta os votos de um nó em todas as propostas.
+    pub fn remove_voter(&mut self, node: &NodeId) {
+        self.votes.values_mut().for_each(|m| { m.remove(node); });
+        self.signed.values_mut().for_each(|m| { m.remove(node); });
+    }
+
+    /// Descarta todos os votos de uma proposta.
+    pub fn remove_proposal(&mut self, proposal_id: &str) {
+        self.votes.remove(proposal_id);
+        self.signed.remove(proposal_id);
+    }
+
+    /// Retorna os votos assinados de uma proposta.
+    pub fn signed_votes(&self, proposal_id: &str) -> Vec<VoteData> {
+        self.signed
+            .get(proposal_id)
+            .map(|m| m.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
     /// Retorna a quantidade de votos "Yes" para uma proposta.
     pub fn count_yes(&self, proposal_id: &str) -> usize {
         self.votes
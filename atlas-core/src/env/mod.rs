@@ -1,5 +1,8 @@
 pub use atlas_sdk::env::*;
+pub mod clock;
 pub mod config;
+pub mod retention;
 pub mod runtime;
 pub mod consensus;
 pub mod storage;
+pub mod timing;
@@ -0,0 +1,132 @@
+//! retention.rs
+//!
+//! Disk retention for the files a node accumulates over time.
+//!
+//! Logs roll over daily and divergence dumps are written once per incident;
+//! neither is needed forever. A periodic janitor deletes files older than
+//! the configured age for each kind and reports how much space the node's
+//! data directory still uses. The audit file is the node's ledger and is
+//! never touched.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Maximum age, in days, of each kind of file. `0` keeps files forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Rolled-over log files under `logs/`.
+    pub log_days: u32,
+    /// State divergence dumps (`divergence-*.json`).
+    pub divergence_dump_days: u32,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self { log_days: 14, divergence_dump_days: 30 }
+    }
+}
+
+/// Outcome of a janitor pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionReport {
+    /// Files deleted in this pass.
+    pub removed_files: usize,
+    /// Bytes freed in this pass.
+    pub removed_bytes: u64,
+    /// Bytes used by retained logs and dumps after the pass.
+    pub retained_bytes: u64,
+}
+
+impl RetentionPolicy {
+    /// Deletes expired logs and dumps under `data_dir`, as of `now`.
+    ///
+    /// Missing directories are not an error: a fresh node has no logs yet.
+    pub fn enforce(&self, data_dir: &Path, now: SystemTime) -> io::Result<RetentionReport> {
+        let mut report = RetentionReport::default();
+
+        sweep(&data_dir.join("logs"), self.log_days, now, |_| true, &mut report)?;
+        sweep(
+            data_dir,
+            self.divergence_dump_days,
+            now,
+            |name| name.starts_with("divergence-") && name.ends_with(".json"),
+            &mut report,
+        )?;
+
+        Ok(report)
+    }
+}
+
+fn sweep(
+    dir: &Path,
+    days: u32,
+    now: SystemTime,
+    matches: impl Fn(&str) -> bool,
+    report: &mut RetentionReport,
+) -> io::Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let max_age = DAY * days;
+
+    for entry in entries {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if !meta.is_file() || !matches(&entry.file_name().to_string_lossy()) {
+            continue;
+        }
+
+        let age = meta.modified().ok().and_then(|m| now.duration_since(m).ok()).unwrap_or_default();
+        if days > 0 && age > max_age {
+            fs::remove_file(entry.path())?;
+            report.removed_files += 1;
+            report.removed_bytes += meta.len();
+        } else {
+            report.retained_bytes += meta.len();
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enforce_removes_only_expired_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("logs")).unwrap();
+        fs::write(dir.path().join("logs/consensus-n1.log.2024-01-01"), b"old").unwrap();
+        fs::write(dir.path().join("divergence-n1-1.json"), b"{}").unwrap();
+        fs::write(dir.path().join("audit-n1.json"), b"{}").unwrap();
+
+        let policy = RetentionPolicy { log_days: 1, divergence_dump_days: 0 };
+
+        let report = policy.enforce(dir.path(), SystemTime::now()).unwrap();
+        assert_eq!(report.removed_files, 0);
+        assert_eq!(report.retained_bytes, 5);
+
+        let later = SystemTime::now() + DAY * 2;
+        let report = policy.enforce(dir.path(), later).unwrap();
+        assert_eq!(report, RetentionReport { removed_files: 1, removed_bytes: 3, retained_bytes: 2 });
+        assert!(dir.path().join("audit-n1.json").exists());
+        assert!(dir.path().join("divergence-n1-1.json").exists(), "0 days keeps dumps forever");
+    }
+
+    #[test]
+    fn test_enforce_tolerates_missing_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = RetentionPolicy::default().enforce(&dir.path().join("nope"), SystemTime::now()).unwrap();
+        assert_eq!(report, RetentionReport::default());
+    }
+}
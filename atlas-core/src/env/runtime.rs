@@ -6,9 +6,10 @@ use std::{
 use serde_json::Value;
 
 use tokio::sync::{Mutex, RwLock};
-use tracing::{info, warn};
+use tracing::info;
 
 use crate::{
+    error::AtlasError,
     peer_manager::PeerManager, 
 };
 use atlas_sdk::utils::NodeId;
@@ -41,6 +42,7 @@ impl AtlasEnv {
         let policy = QuorumPolicy {
             fraction: 0.7,
             min_voters: 1,
+            ..Default::default()
         };
         let engine = ConsensusEngine::new(Arc::clone(&peer_manager), policy);
         AtlasEnv {
@@ -59,12 +61,6 @@ impl AtlasEnv {
             .evaluate_proposals()
             .await
             .into_iter()
-            .map(|res| {
-                // Async lock inside map might be tricky, but let's try
-                // Actually, map is synchronous. We need to lock before or use block_on (bad).
-                // Better: collect results first, then iterate and log.
-                res
-            })
             .collect::<Vec<_>>();
 
         for res in &result {
@@ -94,11 +90,12 @@ impl AtlasEnv {
         }
     }
 
-    pub async fn export_audit(&self, path: &str) {
+    /// Writes the audit file, returning the error instead of logging it so
+    /// callers can refuse to treat an unpersisted commit as done.
+    pub async fn export_audit(&self, path: &str) -> Result<(), AtlasError> {
         let audit = self.storage.read().await.to_audit();
-        if let Err(err) = save_audit(path, &audit) {
-            warn!("Warning: failed to export audit data to {}: {}", path, err);
-        }
+        save_audit(path, &audit)
+            .map_err(|e| AtlasError::Storage(format!("failed to export audit data to {}: {}", path, e)))
     }
 
     pub async fn get_nodes(&self) -> HashSet<NodeId> {
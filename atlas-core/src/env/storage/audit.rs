@@ -9,7 +9,7 @@ use crate::{
 
 use atlas_sdk::{
     utils::NodeId,
-    env::consensus::types::{ConsensusResult, Vote},
+    env::consensus::{certificate::QuorumCertificate, types::{ConsensusResult, Vote}},
 };
 
 /// Structure that represents the full audit data of a consensus session.
@@ -28,10 +28,27 @@ pub struct AuditData {
 
     /// Mapping of proposal ID to the final consensus result.
     pub results: HashMap<String, ConsensusResult>,
+
+    /// Mapping of proposal ID to the quorum certificate that finalized it.
+    #[serde(default)]
+    pub certificates: HashMap<String, QuorumCertificate>,
+
+    /// Mapping of proposal ID to every vote received for it, with reception time.
+    #[serde(default)]
+    pub vote_log: HashMap<String, Vec<super::VoteRecord>>,
+
+    /// Mapping of proposal ID to its proposer, for every proposal that reached the pool.
+    #[serde(default)]
+    pub pooled: HashMap<String, NodeId>,
 }
 
 /// Saves audit data to a JSON file in pretty format.
 ///
+/// The data is written to `<path>.tmp`, flushed to disk and then renamed
+/// over `path`, so a crash mid-write leaves either the previous or the new
+/// audit on disk, never a truncated one. A stale `.tmp` left by a crash is
+/// simply overwritten by the next save.
+///
 /// # Parameters
 /// - `path`: The path to the file where the data will be written.
 /// - `data`: Reference to the `AuditData` to be saved.
@@ -39,8 +56,15 @@ pub struct AuditData {
 /// # Returns
 /// `Ok(())` on success, or an I/O error if the operation fails.
 pub fn save_audit(path: &str, data: &AuditData) -> std::io::Result<()> {
+    use std::io::Write;
+
     let json = serde_json::to_string_pretty(data)?;
-    fs::write(path, json)?;
+    let tmp = format!("{}.tmp", path);
+
+    let mut file = fs::File::create(&tmp)?;
+    file.write_all(json.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)?;
     Ok(())
 }
 
@@ -82,6 +106,7 @@ mod tests {
             parent: None,
             signature: [0u8; 64],
             public_key: vec![],
+            request_id: None,
         };
         proposals.push(proposal.clone());
 
@@ -102,6 +127,9 @@ mod tests {
             proposals,
             votes,
             results,
+            certificates: HashMap::new(),
+            vote_log: HashMap::new(),
+            pooled: HashMap::new(),
         };
 
         // Save to a temporary file
@@ -120,6 +148,23 @@ mod tests {
         let loaded_proposal = &loaded.proposals[0];
         assert_eq!(loaded_proposal.id, "prop-123");
         assert_eq!(loaded.votes["prop-123"][&NodeId("node-A".to_string())], Vote::Yes);
-        assert_eq!(loaded.results["prop-123"].approved, true);
+        assert!(loaded.results["prop-123"].approved);
+    }
+
+    #[test]
+    fn test_save_audit_replaces_file_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.json");
+        let path = path.to_str().unwrap();
+
+        save_audit(path, &AuditData::default()).unwrap();
+
+        // A torn write from a crash only ever reaches the temp file.
+        fs::write(format!("{}.tmp", path), "{\"proposals\": [").unwrap();
+        assert!(load_audit(path).is_ok());
+
+        save_audit(path, &AuditData::default()).unwrap();
+        assert!(load_audit(path).is_ok());
+        assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
     }
 }
@@ -0,0 +1,181 @@
+//! diff.rs
+//!
+//! Ledger comparison between two storage snapshots.
+//!
+//! Used to check that an upgrade or migration left the ledger alone:
+//! load the audit file written before and after, and every proposal whose
+//! content, outcome or certificate changed is reported. Vote traces are
+//! not compared, since nodes legitimately see different votes.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::{StateRoot, Storage};
+use crate::env::proposal::Proposal;
+
+/// A single proposal that differs between two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateDifference {
+    /// Stored only in the first snapshot.
+    OnlyInFirst { proposal_id: String },
+
+    /// Stored only in the second snapshot.
+    OnlyInSecond { proposal_id: String },
+
+    /// Stored in both, but proposer, content, parent or signature differ.
+    ProposalChanged { proposal_id: String },
+
+    /// The consensus outcome differs (`None` when no result is stored).
+    OutcomeChanged {
+        proposal_id: String,
+        first: Option<bool>,
+        second: Option<bool>,
+    },
+
+    /// The quorum certificates list different signers.
+    CertificateChanged { proposal_id: String },
+}
+
+impl std::fmt::Display for StateDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let outcome = |o: &Option<bool>| match o {
+            Some(true) => "approved",
+            Some(false) => "rejected",
+            None => "no result",
+        };
+        match self {
+            StateDifference::OnlyInFirst { proposal_id } => {
+                write!(f, "proposal [{}] only in first snapshot", proposal_id)
+            }
+            StateDifference::OnlyInSecond { proposal_id } => {
+                write!(f, "proposal [{}] only in second snapshot", proposal_id)
+            }
+            StateDifference::ProposalChanged { proposal_id } => {
+                write!(f, "proposal [{}] changed", proposal_id)
+            }
+            StateDifference::OutcomeChanged { proposal_id, first, second } => write!(
+                f,
+                "proposal [{}] outcome changed: {} -> {}",
+                proposal_id, outcome(first), outcome(second)
+            ),
+            StateDifference::CertificateChanged { proposal_id } => {
+                write!(f, "proposal [{}] certificate signers changed", proposal_id)
+            }
+        }
+    }
+}
+
+/// Outcome of comparing two snapshots.
+#[derive(Debug, Clone)]
+pub struct StateDiff {
+    pub first_root: StateRoot,
+    pub second_root: StateRoot,
+
+    /// Differences, ordered by proposal ID.
+    pub differences: Vec<StateDifference>,
+}
+
+impl StateDiff {
+    /// Returns `true` when the ledgers hold the same proposals and outcomes.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+fn same_proposal(a: &Proposal, b: &Proposal) -> bool {
+    a.proposer == b.proposer && a.content == b.content && a.parent == b.parent && a.signature == b.signature
+}
+
+impl Storage {
+    /// Compares this ledger (first) against `other` (second), proposal by proposal.
+    pub fn diff(&self, other: &Storage) -> StateDiff {
+        let index = |s: &Storage| -> HashMap<String, Proposal> {
+            s.proposals.iter().map(|p| (p.id.clone(), p.clone())).collect()
+        };
+        let (first, second) = (index(self), index(other));
+        let ids: BTreeSet<&String> = first.keys().chain(second.keys()).collect();
+
+        let mut differences = Vec::new();
+        for id in ids {
+            let proposal_id = id.clone();
+            match (first.get(id), second.get(id)) {
+                (Some(_), None) => {
+                    differences.push(StateDifference::OnlyInFirst { proposal_id });
+                    continue;
+                }
+                (None, Some(_)) => {
+                    differences.push(StateDifference::OnlyInSecond { proposal_id });
+                    continue;
+                }
+                (Some(a), Some(b)) if !same_proposal(a, b) => {
+                    differences.push(StateDifference::ProposalChanged { proposal_id: proposal_id.clone() });
+                }
+                _ => {}
+            }
+
+            let (a, b) = (self.results.get(id).map(|r| r.approved), other.results.get(id).map(|r| r.approved));
+            if a != b {
+                differences.push(StateDifference::OutcomeChanged { proposal_id: proposal_id.clone(), first: a, second: b });
+            }
+
+            let signers = |s: &Storage| s.certificates.get(id).map(|qc| {
+                qc.votes.iter().map(|v| v.voter.clone()).collect::<BTreeSet<_>>()
+            });
+            if signers(self) != signers(other) {
+                differences.push(StateDifference::CertificateChanged { proposal_id });
+            }
+        }
+
+        StateDiff {
+            first_root: self.state_root(),
+            second_root: other.state_root(),
+            differences,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atlas_sdk::{env::consensus::types::ConsensusResult, utils::NodeId};
+
+    fn proposal(id: &str, content: &str) -> Proposal {
+        Proposal {
+            id: id.into(),
+            proposer: NodeId("n1".into()),
+            content: content.into(),
+            parent: None,
+            signature: [0u8; 64],
+            public_key: Vec::new(),
+            request_id: None,
+        }
+    }
+
+    fn approve(store: &mut Storage, id: &str, approved: bool) {
+        store.log_result(id, ConsensusResult { proposal_id: id.into(), approved, votes_received: 2 });
+    }
+
+    #[test]
+    fn test_diff_reports_changed_missing_and_flipped_proposals() {
+        let mut a = Storage::new();
+        let mut b = Storage::new();
+        for store in [&mut a, &mut b] {
+            store.log_proposal(proposal("p1", "same"));
+            approve(store, "p1", true);
+        }
+        assert!(a.diff(&b).is_empty());
+
+        a.log_proposal(proposal("p2", "before"));
+        approve(&mut a, "p2", true);
+        b.log_proposal(proposal("p2", "after"));
+        approve(&mut b, "p2", false);
+        b.log_proposal(proposal("p3", "new"));
+
+        let diff = a.diff(&b);
+        assert_ne!(diff.first_root, diff.second_root);
+        assert_eq!(diff.differences, vec![
+            StateDifference::ProposalChanged { proposal_id: "p2".into() },
+            StateDifference::OutcomeChanged { proposal_id: "p2".into(), first: Some(true), second: Some(false) },
+            StateDifference::OnlyInSecond { proposal_id: "p3".into() },
+        ]);
+    }
+}
@@ -7,10 +7,13 @@
 //! integration with real persistence mechanisms (e.g., database, disk, etc.).
 //! 
 pub mod audit;
+pub mod diff;
+pub mod scrub;
 
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use audit::AuditData;
 
@@ -20,7 +23,7 @@ use super::{
 
 use atlas_sdk::{
     utils::NodeId,
-    env::consensus::types::{ConsensusResult, Vote},
+    env::consensus::{certificate::QuorumCertificate, types::{ConsensusResult, Vote}},
 };
 
 /// In-memory simulation of a distributed storage ledger.
@@ -36,6 +39,74 @@ pub struct Storage {
 
     /// Map of proposal ID → final consensus result.
     pub results: HashMap<String, ConsensusResult>,
+
+    /// Map of proposal ID → quorum certificate proving its approval.
+    #[serde(default)]
+    pub certificates: HashMap<String, QuorumCertificate>,
+
+    /// Map of proposal ID → every signed vote received for it, in arrival order.
+    #[serde(default)]
+    pub vote_log: HashMap<String, Vec<VoteRecord>>,
+
+    /// Map of proposal ID → proposer, for proposals still waiting in the
+    /// consensus pool. Entries leave when the proposal is finalized (it is
+    /// then in [`proposals`](Self::proposals)) or pruned, so the map stays
+    /// as small as the pool.
+    #[serde(default)]
+    pub pooled: HashMap<String, NodeId>,
+}
+
+/// Most vote records kept per proposal in [`Storage::vote_log`].
+pub const MAX_VOTE_RECORDS: usize = 256;
+
+/// A vote as received by this node, kept for transparency and misbehavior analysis.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VoteRecord {
+    pub voter: NodeId,
+    pub vote: Vote,
+
+    /// Reception time, in milliseconds since the Unix epoch.
+    pub received_at_ms: u64,
+}
+
+/// Digest of the committed state, exchanged between nodes to detect divergence.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateRoot {
+    /// Number of approved proposals covered by the root.
+    pub height: u64,
+
+    /// SHA-256 over the approved proposals, see [`Storage::state_root`].
+    pub root: [u8; 32],
+}
+
+/// Voters whose signed `Yes` votes finalized a committed proposal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attestation {
+    /// Position of the proposal among approved proposals, in commit order (1-based).
+    pub height: u64,
+
+    pub proposal_id: String,
+
+    /// Voters found in the proposal's quorum certificate, in voter order.
+    /// Empty for proposals committed before certificates were stored.
+    pub signers: Vec<NodeId>,
+}
+
+/// A node's consensus record as seen in this node's ledger.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidatorStats {
+    /// Proposals authored by the node that reached the pool.
+    pub proposed: u64,
+
+    /// Authored proposals that were approved.
+    pub committed: u64,
+
+    /// Proposals the node voted on, counting only votes accepted by the
+    /// consensus engine (first vote only).
+    pub votes_cast: u64,
+
+    /// Quorum certificates carrying the node's signature.
+    pub attested: u64,
 }
 
 impl Storage {
@@ -52,6 +123,22 @@ impl Storage {
         self.proposals.push(proposal);
     }
 
+    /// Notes that `proposal` was accepted into the consensus pool.
+    ///
+    /// Unlike [`log_proposal`](Self::log_proposal), which only sees finalized
+    /// proposals, this feeds [`ValidatorStats::proposed`] while the proposal
+    /// is pending.
+    pub fn log_pooled(&mut self, proposal: &Proposal) {
+        self.pooled.insert(proposal.id.clone(), proposal.proposer.clone());
+    }
+
+    /// Drops pool entries once their proposals were finalized or pruned.
+    pub fn forget_pooled<'a>(&mut self, proposal_ids: impl IntoIterator<Item = &'a String>) {
+        for id in proposal_ids {
+            self.pooled.remove(id);
+        }
+    }
+
     /// Logs a vote submitted by a node for a given proposal.
     ///
     /// Votes are stored per proposal and are associated with the node that cast them.
@@ -63,6 +150,21 @@ impl Storage {
             .insert(node, vote);
     }
 
+    /// Appends a received vote to the vote log of its proposal.
+    ///
+    /// Unlike [`log_vote`](Self::log_vote), nothing is overwritten: a voter
+    /// that changes its vote shows up twice. Repeats of a vote already logged
+    /// are dropped, and at most [`MAX_VOTE_RECORDS`] records are kept per
+    /// proposal. Returns whether the record was appended.
+    pub fn log_vote_record(&mut self, proposal_id: &str, record: VoteRecord) -> bool {
+        let log = self.vote_log.entry(proposal_id.to_string()).or_default();
+        if log.len() >= MAX_VOTE_RECORDS || log.iter().any(|r| r.voter == record.voter && r.vote == record.vote) {
+            return false;
+        }
+        log.push(record);
+        true
+    }
+
     /// Logs the final consensus result for a given proposal.
     ///
     /// Typically called after quorum evaluation is complete.
@@ -75,6 +177,16 @@ impl Storage {
         self.results.insert(proposal_id.to_string(), result);
     }
 
+    /// Stores the quorum certificate of an approved proposal.
+    pub fn log_certificate(&mut self, certificate: QuorumCertificate) {
+        println!(
+            "🔏 Storing quorum certificate for [{}] ({} votes)",
+            certificate.proposal_id,
+            certificate.votes.len()
+        );
+        self.certificates.insert(certificate.proposal_id.clone(), certificate);
+    }
+
     /// Prints a summary report of all proposals and their outcomes.
     ///
     /// This is primarily for debugging or auditing purposes.
@@ -96,11 +208,107 @@ impl Storage {
         }
     }
 
+    /// Computes the digest of all approved proposals.
+    ///
+    /// Proposals are hashed by ID, content and signature in ID order, so
+    /// two nodes holding the same committed set agree on the root regardless
+    /// of the order in which they committed it.
+    pub fn state_root(&self) -> StateRoot {
+        let mut approved: Vec<&Proposal> = self.proposals
+            .iter()
+            .filter(|p| self.results.get(&p.id).is_some_and(|r| r.approved))
+            .collect();
+        approved.sort_by(|a, b| a.id.cmp(&b.id));
+        approved.dedup_by(|a, b| a.id == b.id);
+
+        let mut hasher = Sha256::new();
+        for proposal in &approved {
+            for field in [proposal.id.as_bytes(), proposal.content.as_bytes(), &proposal.signature[..]] {
+                hasher.update((field.len() as u64).to_le_bytes());
+                hasher.update(field);
+            }
+        }
+
+        StateRoot {
+            height: approved.len() as u64,
+            root: hasher.finalize().into(),
+        }
+    }
+
+    /// Approved proposals in commit order, with their 1-based height.
+    pub fn chain(&self) -> impl Iterator<Item = (u64, &Proposal)> {
+        let mut seen = std::collections::HashSet::new();
+
+        self.proposals
+            .iter()
+            .filter(move |p| self.results.get(&p.id).is_some_and(|r| r.approved) && seen.insert(p.id.as_str()))
+            .enumerate()
+            .map(|(i, p)| (i as u64 + 1, p))
+    }
+
+    /// Signers of the last `limit` certified commits, newest first.
+    ///
+    /// Approved proposals stored without a certificate are skipped.
+    pub fn recent_signers(&self, limit: usize) -> Vec<std::collections::HashSet<NodeId>> {
+        let chain: Vec<&Proposal> = self.chain().map(|(_, p)| p).collect();
+        chain
+            .into_iter()
+            .rev()
+            .filter_map(|p| self.certificates.get(&p.id))
+            .take(limit)
+            .map(|qc| qc.votes.iter().map(|v| v.voter.clone()).collect())
+            .collect()
+    }
+
+    /// Lists the signers of every approved proposal, in commit order.
+    pub fn attestations(&self) -> Vec<Attestation> {
+        self.chain()
+            .map(|(height, p)| Attestation {
+                height,
+                proposal_id: p.id.clone(),
+                signers: self.certificates
+                    .get(&p.id)
+                    .map(|qc| qc.votes.iter().map(|v| v.voter.clone()).collect())
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Tallies the consensus record of `node` over the whole ledger.
+    pub fn validator_stats(&self, node: &NodeId) -> ValidatorStats {
+        // pending proposals plus every finalized one, approved or not
+        let authored: std::collections::HashSet<&str> = self.pooled
+            .iter()
+            .filter(|(_, proposer)| *proposer == node)
+            .map(|(id, _)| id.as_str())
+            .chain(self.proposals.iter().filter(|p| &p.proposer == node).map(|p| p.id.as_str()))
+            .collect();
+
+        ValidatorStats {
+            proposed: authored.len() as u64,
+            committed: authored
+                .iter()
+                .filter(|id| self.results.get(**id).is_some_and(|r| r.approved))
+                .count() as u64,
+            votes_cast: self.vote_log
+                .values()
+                .filter(|log| log.iter().any(|r| &r.voter == node))
+                .count() as u64,
+            attested: self.certificates
+                .values()
+                .filter(|qc| qc.votes.iter().any(|v| &v.voter == node))
+                .count() as u64,
+        }
+    }
+
     pub fn to_audit(&self) -> AuditData {
         AuditData {
             proposals: self.proposals.clone(),
             votes: self.votes.clone(),
             results: self.results.clone(),
+            certificates: self.certificates.clone(),
+            vote_log: self.vote_log.clone(),
+            pooled: self.pooled.clone(),
         }
     }
 
@@ -108,6 +316,9 @@ impl Storage {
         self.proposals = data.proposals;
         self.votes = data.votes;
         self.results = data.results;
+        self.certificates = data.certificates;
+        self.vote_log = data.vote_log;
+        self.pooled = data.pooled;
     }
 }
 
@@ -130,6 +341,7 @@ mod tests {
             parent: None,
             signature: [0u8; 64],
             public_key: vec![],
+            request_id: None,
         }
     }
 
@@ -174,7 +386,7 @@ mod tests {
         store.log_result("p42", result.clone());
 
         assert!(store.results.contains_key("p42"));
-        assert_eq!(store.results["p42"].approved, true);
+        assert!(store.results["p42"].approved);
         assert_eq!(store.results["p42"].votes_received, 3);
     }
 
@@ -189,6 +401,64 @@ mod tests {
         assert_eq!(votes.get(&node("n1")), Some(&Vote::Yes));
     }
 
+    #[test]
+    fn test_validator_stats_counts_authored_and_voted() {
+        let mut store = Storage::new();
+        store.log_proposal(sample_proposal("p1", "n1", "a"));
+        store.log_proposal(sample_proposal("p2", "n1", "b"));
+        store.log_result("p1", sample_result(true, 1, "p1"));
+        store.log_vote_record("p1", VoteRecord { voter: node("n2"), vote: Vote::Yes, received_at_ms: 1 });
+        store.log_vote_record("p1", VoteRecord { voter: node("n2"), vote: Vote::Yes, received_at_ms: 2 });
+
+        assert_eq!(
+            store.validator_stats(&node("n1")),
+            ValidatorStats { proposed: 2, committed: 1, votes_cast: 0, attested: 0 }
+        );
+        assert_eq!(store.validator_stats(&node("n2")).votes_cast, 1);
+    }
+
+    #[test]
+    fn test_validator_stats_counts_pooled_proposals_that_never_committed() {
+        let mut store = Storage::new();
+        let committed = sample_proposal("p1", "n1", "a");
+        let rejected = sample_proposal("p2", "n1", "b");
+        store.log_pooled(&committed);
+        store.log_pooled(&rejected);
+        store.log_pooled(&sample_proposal("p3", "n1", "c"));
+        store.log_pooled(&sample_proposal("p4", "n1", "d"));
+        store.log_pooled(&committed);
+
+        store.log_proposal(committed);
+        store.log_result("p1", sample_result(true, 1, "p1"));
+        store.log_proposal(rejected);
+        store.log_result("p2", sample_result(false, 1, "p2"));
+        // p1 and p2 were finalized, p4 was pruned; only p3 is still pending
+        store.forget_pooled(&["p1".to_string(), "p2".to_string(), "p4".to_string()]);
+        assert_eq!(store.pooled.len(), 1);
+
+        let stats = store.validator_stats(&node("n1"));
+        assert_eq!((stats.proposed, stats.committed), (3, 1));
+
+        let mut restored = Storage::new();
+        restored.apply_audit(store.to_audit());
+        assert_eq!(restored.validator_stats(&node("n1")), stats);
+    }
+
+    #[test]
+    fn test_vote_log_keeps_every_vote_and_survives_audit() {
+        let mut store = Storage::new();
+        store.log_vote_record("p1", VoteRecord { voter: node("n1"), vote: Vote::No, received_at_ms: 10 });
+        store.log_vote_record("p1", VoteRecord { voter: node("n1"), vote: Vote::Yes, received_at_ms: 20 });
+
+        let mut restored = Storage::new();
+        restored.apply_audit(store.to_audit());
+
+        let log = restored.vote_log.get("p1").unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].vote, Vote::No);
+        assert_eq!(log[1].received_at_ms, 20);
+    }
+
     #[test]
     fn test_print_summary_handles_all_states() {
         let mut store = Storage::new();
@@ -208,8 +478,52 @@ mod tests {
         // Isso imprime no stdout, mas não afeta assertivas aqui.
         store.print_summary();
 
-        assert_eq!(store.results["p1"].approved, true);
-        assert_eq!(store.results["p2"].approved, false);
+        assert!(store.results["p1"].approved);
+        assert!(!store.results["p2"].approved);
         assert!(!store.results.contains_key("p3")); // sem resultado ainda
     }
+
+    #[test]
+    fn test_state_root_ignores_commit_order() {
+        let mut a = Storage::new();
+        let mut b = Storage::new();
+
+        for (store, order) in [(&mut a, ["p1", "p2", "p3"]), (&mut b, ["p3", "p1", "p2"])] {
+            for id in order {
+                store.log_proposal(sample_proposal(id, "n1", id));
+                store.log_result(id, sample_result(id != "p3", 2, id));
+            }
+        }
+
+        assert_eq!(a.state_root(), b.state_root());
+        assert_eq!(a.state_root().height, 2, "rejected proposals are not part of the root");
+
+        b.proposals[1].content = "tampered".into(); // p1
+        assert_ne!(a.state_root(), b.state_root());
+    }
+
+    #[test]
+    fn test_attestations_follow_commit_order() {
+        let mut store = Storage::new();
+        for (id, approved) in [("p2", true), ("p9", false), ("p1", true)] {
+            store.log_proposal(sample_proposal(id, "n1", id));
+            store.log_result(id, sample_result(approved, 2, id));
+        }
+        store.log_certificate(QuorumCertificate {
+            proposal_id: "p1".into(),
+            votes: vec![atlas_sdk::env::vote_data::VoteData {
+                proposal_id: "p1".into(),
+                vote: Vote::Yes,
+                voter: node("n2"),
+                signature: [0u8; 64],
+                public_key: vec![],
+            }],
+        });
+
+        let att = store.attestations();
+        assert_eq!(att.len(), 2);
+        assert_eq!((att[0].height, att[0].proposal_id.as_str()), (1, "p2"));
+        assert!(att[0].signers.is_empty());
+        assert_eq!((att[1].height, att[1].signers.clone()), (2, vec![node("n2")]));
+    }
 }
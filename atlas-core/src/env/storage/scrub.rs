@@ -0,0 +1,276 @@
+//! scrub.rs
+//!
+//! Consistency checks over the storage ledger.
+//!
+//! The scrubber walks stored proposals, votes and results looking for
+//! records that do not line up with each other, so that corruption is
+//! reported in the logs, metrics and node info before it surfaces as a failed commit or audit.
+
+use std::collections::HashSet;
+
+use atlas_sdk::env::consensus::types::Vote;
+
+use super::Storage;
+
+/// A single discrepancy found while scrubbing the storage ledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScrubIssue {
+    /// The same proposal ID was stored more than once.
+    DuplicateProposal { proposal_id: String },
+
+    /// Votes or a quorum certificate were recorded for a proposal that is
+    /// not stored.
+    OrphanVotes { proposal_id: String },
+
+    /// A consensus result was recorded for a proposal that is not stored.
+    OrphanResult { proposal_id: String },
+
+    /// The stored result disagrees with the `Yes` votes backing it: the
+    /// certificate signers, or the vote log when it holds fewer `Yes`
+    /// voters than the result claims.
+    VoteCountMismatch {
+        proposal_id: String,
+        recorded: usize,
+        counted: usize,
+    },
+
+    /// The proposal signature does not verify against its public key.
+    InvalidSignature { proposal_id: String },
+}
+
+impl std::fmt::Display for ScrubIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScrubIssue::DuplicateProposal { proposal_id } => {
+                write!(f, "duplicate proposal [{}]", proposal_id)
+            }
+            ScrubIssue::OrphanVotes { proposal_id } => {
+                write!(f, "votes for unknown proposal [{}]", proposal_id)
+            }
+            ScrubIssue::OrphanResult { proposal_id } => {
+                write!(f, "result for unknown proposal [{}]", proposal_id)
+            }
+            ScrubIssue::VoteCountMismatch { proposal_id, recorded, counted } => write!(
+                f,
+                "proposal [{}] result records {} votes but {} 'Yes' votes back it",
+                proposal_id, recorded, counted
+            ),
+            ScrubIssue::InvalidSignature { proposal_id } => {
+                write!(f, "invalid signature on proposal [{}]", proposal_id)
+            }
+        }
+    }
+}
+
+/// Outcome of a scrub pass.
+#[derive(Debug, Clone, Default)]
+pub struct ScrubReport {
+    /// Number of proposals inspected.
+    pub proposals_checked: usize,
+
+    /// All discrepancies found, in discovery order.
+    pub issues: Vec<ScrubIssue>,
+}
+
+impl ScrubReport {
+    /// Returns `true` when no discrepancies were found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl Storage {
+    /// Checks that proposals, votes and results reference each other consistently.
+    ///
+    /// Signatures are not checked here since the storage has no access to an
+    /// authenticator; see `Cluster::scrub_storage` for the full pass.
+    pub fn scrub(&self) -> ScrubReport {
+        let mut report = ScrubReport::default();
+        let mut seen = HashSet::new();
+
+        for proposal in &self.proposals {
+            report.proposals_checked += 1;
+            if !seen.insert(proposal.id.as_str()) {
+                report.issues.push(ScrubIssue::DuplicateProposal {
+                    proposal_id: proposal.id.clone(),
+                });
+            }
+        }
+
+        let mut vote_ids: Vec<&String> = self.votes.keys().chain(self.certificates.keys()).collect();
+        vote_ids.sort();
+        vote_ids.dedup();
+        for proposal_id in vote_ids {
+            if !seen.contains(proposal_id.as_str()) {
+                report.issues.push(ScrubIssue::OrphanVotes {
+                    proposal_id: proposal_id.clone(),
+                });
+            }
+        }
+
+        let mut result_ids: Vec<&String> = self.results.keys().collect();
+        result_ids.sort();
+        for proposal_id in result_ids {
+            if !seen.contains(proposal_id.as_str()) {
+                report.issues.push(ScrubIssue::OrphanResult {
+                    proposal_id: proposal_id.clone(),
+                });
+                continue;
+            }
+
+            let result = &self.results[proposal_id];
+            if !result.approved {
+                continue;
+            }
+            let recorded = result.votes_received;
+
+            // The certificate carries exactly the votes that approved the proposal.
+            if let Some(certificate) = self.certificates.get(proposal_id) {
+                let counted = certificate.votes.len();
+                if counted != recorded {
+                    report.issues.push(ScrubIssue::VoteCountMismatch {
+                        proposal_id: proposal_id.clone(),
+                        recorded,
+                        counted,
+                    });
+                    continue;
+                }
+            }
+
+            // Every counted vote went through the vote log; late votes may add more.
+            let counted = self.vote_log.get(proposal_id).map_or(0, |log| {
+                log.iter()
+                    .filter(|r| matches!(r.vote, Vote::Yes))
+                    .map(|r| &r.voter)
+                    .collect::<HashSet<_>>()
+                    .len()
+            });
+            if counted < recorded {
+                report.issues.push(ScrubIssue::VoteCountMismatch {
+                    proposal_id: proposal_id.clone(),
+                    recorded,
+                    counted,
+                });
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atlas_sdk::{
+        env::{
+            consensus::{certificate::QuorumCertificate, types::ConsensusResult},
+            proposal::Proposal,
+            vote_data::VoteData,
+        },
+        utils::NodeId,
+    };
+
+    use crate::env::storage::VoteRecord;
+
+    fn sample_proposal(id: &str) -> Proposal {
+        Proposal {
+            id: id.to_string(),
+            proposer: NodeId("n1".into()),
+            content: "{}".to_string(),
+            parent: None,
+            signature: [0u8; 64],
+            public_key: vec![],
+            request_id: None,
+        }
+    }
+
+    fn sample_result(proposal_id: &str, votes: usize) -> ConsensusResult {
+        ConsensusResult {
+            approved: true,
+            votes_received: votes,
+            proposal_id: proposal_id.to_string(),
+        }
+    }
+
+    fn record_vote(store: &mut Storage, proposal_id: &str, voter: &str, vote: Vote) {
+        store.log_vote_record(proposal_id, VoteRecord { voter: NodeId(voter.into()), vote, received_at_ms: 0 });
+    }
+
+    fn certificate(proposal_id: &str, signers: &[&str]) -> QuorumCertificate {
+        QuorumCertificate::new(proposal_id, signers.iter().map(|voter| VoteData {
+            proposal_id: proposal_id.into(),
+            vote: Vote::Yes,
+            voter: NodeId((*voter).into()),
+            signature: [0u8; 64],
+            public_key: Vec::new(),
+        }))
+    }
+
+    #[test]
+    fn test_scrub_clean_storage() {
+        let mut store = Storage::new();
+        store.log_proposal(sample_proposal("p1"));
+        record_vote(&mut store, "p1", "n1", Vote::Yes);
+        // a vote arriving after the commit is not a discrepancy
+        record_vote(&mut store, "p1", "n2", Vote::Yes);
+        store.log_result("p1", sample_result("p1", 1));
+        store.log_certificate(certificate("p1", &["n1"]));
+
+        let report = store.scrub();
+        assert_eq!(report.proposals_checked, 1);
+        assert!(report.is_clean(), "unexpected issues: {:?}", report.issues);
+    }
+
+    #[test]
+    fn test_scrub_detects_orphans_and_duplicates() {
+        let mut store = Storage::new();
+        store.log_proposal(sample_proposal("p1"));
+        store.log_proposal(sample_proposal("p1"));
+        store.log_vote("ghost", NodeId("n1".into()), Vote::Yes);
+        store.log_certificate(certificate("ghost", &["n1"]));
+        store.log_result("missing", sample_result("missing", 1));
+
+        let report = store.scrub();
+        assert_eq!(
+            report.issues,
+            vec![
+                ScrubIssue::DuplicateProposal { proposal_id: "p1".into() },
+                ScrubIssue::OrphanVotes { proposal_id: "ghost".into() },
+                ScrubIssue::OrphanResult { proposal_id: "missing".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scrub_detects_vote_count_mismatch() {
+        let mut store = Storage::new();
+        store.log_proposal(sample_proposal("p1"));
+        record_vote(&mut store, "p1", "n1", Vote::Yes);
+        record_vote(&mut store, "p1", "n2", Vote::No);
+        store.log_result("p1", sample_result("p1", 2));
+
+        // certificate with fewer signers than the result
+        store.log_proposal(sample_proposal("p2"));
+        record_vote(&mut store, "p2", "n1", Vote::Yes);
+        record_vote(&mut store, "p2", "n2", Vote::Yes);
+        store.log_result("p2", sample_result("p2", 2));
+        store.log_certificate(certificate("p2", &["n1"]));
+
+        let report = store.scrub();
+        assert_eq!(
+            report.issues,
+            vec![
+                ScrubIssue::VoteCountMismatch {
+                    proposal_id: "p1".into(),
+                    recorded: 2,
+                    counted: 1,
+                },
+                ScrubIssue::VoteCountMismatch {
+                    proposal_id: "p2".into(),
+                    recorded: 2,
+                    counted: 1,
+                },
+            ]
+        );
+    }
+}
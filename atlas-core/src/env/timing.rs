@@ -0,0 +1,209 @@
+//! timing.rs
+//!
+//! Per-stage latency tracking for the proposal write path.
+//!
+//! Each proposal accumulates a timestamp per stage it passes through
+//! (ingest, signing, pooling, gossip, approval, storage, persistence). The
+//! time spent in each stage also feeds a per-stage histogram, so slow
+//! stages can be located from live traffic instead of guessed at.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Number of per-proposal timing records kept in memory.
+pub const MAX_TIMING_RECORDS: usize = 1024;
+
+/// Upper bounds (in microseconds) of the histogram buckets.
+/// A final implicit bucket holds everything slower.
+const BUCKET_BOUNDS_US: [u64; 10] = [
+    100, 500, 1_000, 5_000, 10_000, 50_000, 100_000, 500_000, 1_000_000, 5_000_000,
+];
+
+/// A step on the proposal write path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Stage {
+    /// Received through the gRPC API.
+    Ingest,
+    /// Signed by the local node.
+    Signed,
+    /// Added to the local consensus pool.
+    Pooled,
+    /// Handed to the P2P layer for gossip.
+    Published,
+    /// Received from a peer over gossip.
+    Received,
+    /// Reached quorum.
+    Approved,
+    /// Written to storage.
+    Stored,
+    /// Written to the audit file on disk.
+    Persisted,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 8] = [
+        Stage::Ingest,
+        Stage::Signed,
+        Stage::Pooled,
+        Stage::Published,
+        Stage::Received,
+        Stage::Approved,
+        Stage::Stored,
+        Stage::Persisted,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Ingest => "ingest",
+            Stage::Signed => "signed",
+            Stage::Pooled => "pooled",
+            Stage::Published => "published",
+            Stage::Received => "received",
+            Stage::Approved => "approved",
+            Stage::Stored => "stored",
+            Stage::Persisted => "persisted",
+        }
+    }
+}
+
+/// Latency histogram with fixed buckets.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKET_BOUNDS_US.len() + 1],
+    count: u64,
+    sum_us: u64,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency: Duration) {
+        let us = latency.as_micros() as u64;
+        let idx = BUCKET_BOUNDS_US.iter().position(|b| us <= *b).unwrap_or(BUCKET_BOUNDS_US.len());
+        self.buckets[idx] += 1;
+        self.count += 1;
+        self.sum_us = self.sum_us.saturating_add(us);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean_us(&self) -> u64 {
+        self.sum_us.checked_div(self.count).unwrap_or(0)
+    }
+
+    /// Upper bound of the bucket holding quantile `q` (0.0..=1.0).
+    ///
+    /// Samples above the last bound report `u64::MAX`.
+    pub fn quantile_us(&self, q: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let target = ((self.count as f64) * q).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (idx, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= target {
+                return BUCKET_BOUNDS_US.get(idx).copied().unwrap_or(u64::MAX);
+            }
+        }
+        u64::MAX
+    }
+}
+
+/// Stage timestamps recorded for a single proposal.
+#[derive(Debug, Clone)]
+struct TimingRecord {
+    started: Instant,
+    last: Instant,
+    marks: Vec<(Stage, Duration)>,
+}
+
+/// Write-path timings for recent proposals plus per-stage histograms.
+#[derive(Debug, Default)]
+pub struct WritePathTimings {
+    records: HashMap<String, TimingRecord>,
+    order: VecDeque<String>,
+    histograms: HashMap<Stage, LatencyHistogram>,
+}
+
+impl WritePathTimings {
+    /// Records that `proposal_id` reached `stage`.
+    ///
+    /// The first mark of a proposal starts its clock. Later marks feed the
+    /// histogram of their stage with the time since the previous mark.
+    pub fn mark(&mut self, proposal_id: &str, stage: Stage) {
+        let now = Instant::now();
+
+        match self.records.get_mut(proposal_id) {
+            Some(record) => {
+                self.histograms.entry(stage).or_default().record(now - record.last);
+                record.marks.push((stage, now - record.started));
+                record.last = now;
+            }
+            None => {
+                if self.order.len() >= MAX_TIMING_RECORDS {
+                    if let Some(oldest) = self.order.pop_front() {
+                        self.records.remove(&oldest);
+                    }
+                }
+                self.order.push_back(proposal_id.to_string());
+                self.records.insert(
+                    proposal_id.to_string(),
+                    TimingRecord { started: now, last: now, marks: vec![(stage, Duration::ZERO)] },
+                );
+            }
+        }
+    }
+
+    /// Stages reached by `proposal_id`, with the elapsed time since its first mark.
+    pub fn record(&self, proposal_id: &str) -> Vec<(Stage, Duration)> {
+        self.records.get(proposal_id).map(|r| r.marks.clone()).unwrap_or_default()
+    }
+
+    /// Histogram of the time spent reaching `stage`.
+    pub fn histogram(&self, stage: Stage) -> Option<&LatencyHistogram> {
+        self.histograms.get(&stage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marks_accumulate_per_proposal() {
+        let mut timings = WritePathTimings::default();
+        timings.mark("p1", Stage::Ingest);
+        timings.mark("p1", Stage::Pooled);
+        timings.mark("p1", Stage::Stored);
+
+        let stages: Vec<Stage> = timings.record("p1").into_iter().map(|(s, _)| s).collect();
+        assert_eq!(stages, vec![Stage::Ingest, Stage::Pooled, Stage::Stored]);
+        assert_eq!(timings.histogram(Stage::Pooled).unwrap().count(), 1);
+        assert!(timings.histogram(Stage::Ingest).is_none(), "first mark only starts the clock");
+        assert!(timings.record("unknown").is_empty());
+    }
+
+    #[test]
+    fn test_records_are_bounded() {
+        let mut timings = WritePathTimings::default();
+        for i in 0..=MAX_TIMING_RECORDS {
+            timings.mark(&format!("p{}", i), Stage::Received);
+        }
+        assert!(timings.record("p0").is_empty());
+        assert_eq!(timings.records.len(), MAX_TIMING_RECORDS);
+    }
+
+    #[test]
+    fn test_histogram_quantiles() {
+        let mut h = LatencyHistogram::default();
+        for _ in 0..99 {
+            h.record(Duration::from_micros(50));
+        }
+        h.record(Duration::from_secs(10));
+
+        assert_eq!(h.quantile_us(0.5), 100);
+        assert_eq!(h.quantile_us(0.99), 100);
+        assert_eq!(h.quantile_us(1.0), u64::MAX);
+    }
+}
@@ -18,8 +18,9 @@ pub enum AtlasError {
     #[error("Invalid config: {0}")]
     Config(String),
 
+    // Boxed: `tonic::Status` is large enough to bloat every `Result<T>`.
     #[error("gRPC error: {0}")]
-    Grpc(#[from] tonic::Status),
+    Grpc(#[from] Box<tonic::Status>),
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -28,4 +29,10 @@ pub enum AtlasError {
     Other(String),
 }
 
+impl From<tonic::Status> for AtlasError {
+    fn from(status: tonic::Status) -> Self {
+        AtlasError::Grpc(Box::new(status))
+    }
+}
+
 pub type Result<T> = std::result::Result<T, AtlasError>;
@@ -0,0 +1,119 @@
+//! fault.rs
+//!
+//! Fault injection for chaos testing (feature `fault-injection`).
+//!
+//! Faults are process-wide and configured at runtime through the
+//! `SetFaults` RPC. Hooks in the proposal, gossip and commit paths consult
+//! the current settings; with the feature disabled the hooks are compiled
+//! out entirely.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+
+use tracing::{error, warn};
+
+use crate::rpc::atlas::FaultSettings;
+
+static FAULTS: FaultInjector = FaultInjector::new();
+
+/// Returns the process-wide fault injector.
+pub fn faults() -> &'static FaultInjector {
+    &FAULTS
+}
+
+/// Current fault settings plus the counters they act on.
+#[derive(Debug)]
+pub struct FaultInjector {
+    proposal_delay_ms: AtomicU64,
+    gossip_drop_percent: AtomicU32,
+    storage_paused: AtomicBool,
+    crash_after_commits: AtomicU64,
+    commits: AtomicU64,
+}
+
+impl FaultInjector {
+    const fn new() -> Self {
+        Self {
+            proposal_delay_ms: AtomicU64::new(0),
+            gossip_drop_percent: AtomicU32::new(0),
+            storage_paused: AtomicBool::new(false),
+            crash_after_commits: AtomicU64::new(0),
+            commits: AtomicU64::new(0),
+        }
+    }
+
+    /// Replaces the current settings. The commit counter restarts so that
+    /// `crash_after_commits` counts from now.
+    pub fn configure(&self, settings: &FaultSettings) {
+        warn!("🧨 Injeção de falhas configurada: {:?}", settings);
+        self.proposal_delay_ms.store(settings.proposal_delay_ms, Ordering::Relaxed);
+        self.gossip_drop_percent.store(settings.gossip_drop_percent.min(100), Ordering::Relaxed);
+        self.storage_paused.store(settings.pause_storage_writes, Ordering::Relaxed);
+        self.crash_after_commits.store(settings.crash_after_commits, Ordering::Relaxed);
+        self.commits.store(0, Ordering::Relaxed);
+    }
+
+    /// Current settings.
+    pub fn settings(&self) -> FaultSettings {
+        FaultSettings {
+            proposal_delay_ms: self.proposal_delay_ms.load(Ordering::Relaxed),
+            gossip_drop_percent: self.gossip_drop_percent.load(Ordering::Relaxed),
+            pause_storage_writes: self.storage_paused.load(Ordering::Relaxed),
+            crash_after_commits: self.crash_after_commits.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Delays proposal production by the configured amount.
+    pub async fn delay_proposal(&self) {
+        let ms = self.proposal_delay_ms.load(Ordering::Relaxed);
+        if ms > 0 {
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+        }
+    }
+
+    /// Returns `true` if an inbound gossip message should be dropped.
+    pub fn drop_gossip(&self) -> bool {
+        let percent = self.gossip_drop_percent.load(Ordering::Relaxed);
+        percent > 0 && rand::random::<u32>() % 100 < percent
+    }
+
+    /// Blocks while storage writes are paused.
+    pub async fn wait_storage(&self) {
+        while self.storage_paused.load(Ordering::Relaxed) {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    /// Counts a commit and aborts the process once the configured number is reached.
+    pub fn record_commit(&self) {
+        let limit = self.crash_after_commits.load(Ordering::Relaxed);
+        let commits = self.commits.fetch_add(1, Ordering::Relaxed) + 1;
+        if limit > 0 && commits >= limit {
+            error!("🧨 Falha injetada: abortando após {} commits", commits);
+            std::process::abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configure_roundtrip() {
+        let injector = FaultInjector::new();
+        let settings = FaultSettings {
+            proposal_delay_ms: 250,
+            gossip_drop_percent: 150,
+            pause_storage_writes: true,
+            crash_after_commits: 0,
+        };
+        injector.configure(&settings);
+
+        let current = injector.settings();
+        assert_eq!(current.gossip_drop_percent, 100, "clamped to 100%");
+        assert_eq!(current.proposal_delay_ms, 250);
+        assert!(current.pause_storage_writes);
+        assert!(injector.drop_gossip());
+    }
+}
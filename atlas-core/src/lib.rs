@@ -4,10 +4,18 @@ pub mod cluster;
 pub mod config;
 pub mod env;
 pub mod error;
+#[cfg(feature = "fault-injection")]
+pub mod fault;
+pub mod metrics;
 pub mod network;
 pub mod peer_manager;
 pub mod rpc;
 pub mod runtime;
+pub mod selfcheck;
+pub mod support;
+#[cfg(feature = "otlp")]
+pub mod telemetry;
+pub mod version;
 
 pub use cluster::{
     core::Cluster, 
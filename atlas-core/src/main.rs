@@ -3,7 +3,11 @@ use tokio::sync::RwLock;
 use std::path::Path;
 use atlas_sdk::auth::{ed25519::Ed25519Authenticator, Authenticator};
 use atlas_db::network::key_manager;
-use tracing::{info, error};
+use atlas_db::network::capabilities::Capabilities;
+use atlas_db::network::namespace::NetworkNamespace;
+use atlas_db::{selfcheck, support, version};
+use atlas_db::env::storage::{audit::load_audit, Storage};
+use tracing::{info, warn, error};
 
 use atlas_db::network::p2p::config::P2pConfig;
 use atlas_db::runtime::builder::build_runtime;
@@ -13,11 +17,57 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 1. Inicializar o logger
     // 2. Parsear argumentos da linha de comando
     let args: Vec<String> = std::env::args().collect();
-    let p2p_listen_addr = get_arg_value(&args, "--listen").unwrap_or("/ip4/0.0.0.0/tcp/0");
+    // --network <nome> isola diretório de dados, chaves, portas e tópicos P2P
+    let network = NetworkNamespace::from_arg(get_arg_value(&args, "--network"))?;
+    let default_listen = network.default_listen_addr();
+    let default_grpc_port = network.default_grpc_port().to_string();
+    let default_config = network.path("config.json").to_string_lossy().into_owned();
+    let default_keypair = network.path("keys/keypair").to_string_lossy().into_owned();
+
+    let p2p_listen_addr = get_arg_value(&args, "--listen").unwrap_or(&default_listen);
     let dial_addr = get_arg_value(&args, "--dial");
-    let grpc_port = get_arg_value(&args, "--grpc-port").unwrap_or("50051");
-    let config_path = get_arg_value(&args, "--config").unwrap_or("config.json");
-    let keypair_path = get_arg_value(&args, "--keypair").unwrap_or("keys/keypair");
+    let grpc_port = get_arg_value(&args, "--grpc-port").unwrap_or(&default_grpc_port);
+    let config_path = get_arg_value(&args, "--config").unwrap_or(&default_config);
+    let keypair_path = get_arg_value(&args, "--keypair").unwrap_or(&default_keypair);
+    // --capabilities archive,snapshot,faucet,relay: serviços anunciados aos peers
+    let capabilities = Capabilities::from_names(get_arg_value(&args, "--capabilities").unwrap_or_default())?;
+    // --otlp-endpoint http://collector:4317: exporta os spans do consenso (feature `otlp`)
+    let otlp_endpoint = get_arg_value(&args, "--otlp-endpoint");
+
+    // support-bundle: empacota config, peers, estado do storage e logs para bug reports
+    if args.get(1).map(String::as_str) == Some("support-bundle") {
+        let output = get_arg_value(&args, "--output").unwrap_or("support-bundle.tar.gz");
+        let sources = support::BundleSources {
+            config_path: config_path.into(),
+            data_dir: network.data_dir(),
+        };
+        let files = support::write_bundle(&sources, Path::new(output))?;
+        println!("Bundle gravado em {} ({} arquivos)", output, files.len());
+        return Ok(());
+    }
+
+    // statediff <a> <b>: compara dois audits (ex: antes e depois de um upgrade)
+    if args.get(1).map(String::as_str) == Some("statediff") {
+        let (Some(first), Some(second)) = (args.get(2), args.get(3)) else {
+            return Err("uso: statediff <audit-a.json> <audit-b.json>".into());
+        };
+        let load = |path: &str| -> std::io::Result<Storage> {
+            let mut storage = Storage::new();
+            storage.apply_audit(load_audit(path)?);
+            Ok(storage)
+        };
+        let diff = load(first)?.diff(&load(second)?);
+        println!("{}: altura {} root {}", first, diff.first_root.height, hex::encode(diff.first_root.root));
+        println!("{}: altura {} root {}", second, diff.second_root.height, hex::encode(diff.second_root.root));
+        for difference in &diff.differences {
+            println!("  {}", difference);
+        }
+        if !diff.is_empty() {
+            return Err(format!("{} diferenças no ledger", diff.differences.len()).into());
+        }
+        println!("Ledgers idênticos");
+        return Ok(());
+    }
 
     // Extract node name from config path (e.g., "node1/config.json" -> "node1")
     let node_name = std::path::Path::new(config_path)
@@ -26,10 +76,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .and_then(|s| s.to_str())
         .unwrap_or("unknown_node");
 
-    let log_filename = format!("logs/consensus-{}.log", node_name);
+    let log_filename = format!("consensus-{}.log", node_name);
 
-    // 1. Inicializar o logger
-    let file_appender = tracing_appender::rolling::never(".", log_filename);
+    // 1. Inicializar o logger (um arquivo por dia, para a retenção poder apagar os antigos)
+    let file_appender = tracing_appender::rolling::daily(network.data_dir().join("logs"), log_filename);
     let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
 
     use tracing_subscriber::prelude::*;
@@ -45,16 +95,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_filter(tracing_subscriber::EnvFilter::try_from_default_env()
             .unwrap_or_else(|_| "info,atlas_db=debug".into()));
 
+    #[cfg(feature = "otlp")]
+    let otlp_layer = match otlp_endpoint {
+        Some(endpoint) => Some(atlas_db::telemetry::otlp_layer(endpoint, node_name)?),
+        None => None,
+    };
+    #[cfg(not(feature = "otlp"))]
+    let otlp_layer: Option<tracing_subscriber::layer::Identity> = None;
+
     tracing_subscriber::registry()
         .with(consensus_layer)
         .with(stdout_layer)
+        .with(otlp_layer)
         .init();
 
     info!("--- INICIANDO NÓ ATLASDB ---");
+    info!("Versão: {}", version::agent_version());
+    info!("Rede: {}", network);
+    if !capabilities.is_empty() { info!("Capacidades: {}", capabilities); }
     info!("Config: {}", config_path);
     info!("Endereço P2P: {}", p2p_listen_addr);
     if let Some(addr) = dial_addr { info!("Bootstrap (dial): {}", addr); }
     info!("Porta gRPC: {}", grpc_port);
+    if let Some(endpoint) = otlp_endpoint {
+        if cfg!(feature = "otlp") {
+            info!("Exportando spans via OTLP para {}", endpoint);
+        } else {
+            warn!("--otlp-endpoint ignorado: build sem a feature `otlp`");
+        }
+    }
 
     // 2.1 Teste manual de autenticação
     if args.contains(&"--test-auth".to_string()) {
@@ -99,11 +168,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         enable_mdns: true,
         enable_kademlia: true,
         keypair_path: keypair_path.to_string(),
+        network,
+        capabilities,
     };
 
     let grpc_addr_str = format!("0.0.0.0:{}", grpc_port);
     let grpc_addr = grpc_addr_str.parse()?;
 
+    // 3.1 Auto-verificação: aborta antes do consenso se algo crítico falhar
+    let report = selfcheck::run(&selfcheck::SelfCheckInputs {
+        data_dir: p2p_config.network.data_dir(),
+        config_path: config_path.into(),
+        keypair_path: keypair_path.into(),
+        grpc_addr,
+        p2p_listen: p2p_listen_addr.to_string(),
+    });
+    for check in &report.checks {
+        match check.status {
+            selfcheck::CheckStatus::Ok => info!("✅ {}: {}", check.name, check.detail),
+            selfcheck::CheckStatus::Warn => warn!("⚠️ {}: {}", check.name, check.detail),
+            selfcheck::CheckStatus::Fail => error!("❌ {}: {}", check.name, check.detail),
+        }
+    }
+    match report.save(&p2p_config.network.data_dir()) {
+        Ok(path) => info!("Relatório de inicialização gravado em {}", path.display()),
+        Err(e) => warn!("Falha ao gravar relatório de inicialização: {}", e),
+    }
+    if !report.passed {
+        error!("Auto-verificação falhou; abortando antes de entrar no consenso.");
+        return Err("startup self-check failed".into());
+    }
+
     // 4. Construir e iniciar o runtime
     match build_runtime(config_path, auth, p2p_config, grpc_addr).await {
         Ok(_runtime) => {
@@ -0,0 +1,224 @@
+//! metrics.rs
+//!
+//! Prometheus metrics.
+//!
+//! Counters and histograms are updated where the work happens (votes in
+//! the cluster, network events in Maestro, audit writes on commit). Gauges
+//! that mirror cluster state — height, pool size, peers, alarms — are read
+//! from the cluster on each scrape instead of being kept in sync by hand.
+//! Served as text on `/metrics` when `metrics_addr` is set in the config.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use prometheus_client::encoding::{text::encode, EncodeLabelSet};
+use prometheus_client::metrics::{counter::Counter, family::Family, gauge::Gauge, histogram::{exponential_buckets, Histogram}};
+use prometheus_client::registry::Registry;
+
+use atlas_sdk::env::consensus::types::Vote;
+
+use crate::cluster::core::Cluster;
+use crate::network::p2p::events::AdapterEvent;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct VoteLabels {
+    /// `yes`, `no` or `abstain`.
+    pub vote: &'static str,
+    /// `local` for votes cast by this node, `peer` for received ones.
+    pub source: &'static str,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct EventLabels {
+    pub kind: &'static str,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct TopicLabels {
+    /// Logical gossipsub topic, e.g. `atlas/vote/v1`.
+    pub topic: String,
+}
+
+/// Every metric the node exports.
+pub struct Metrics {
+    registry: Registry,
+    pub height: Gauge,
+    pub pool_size: Gauge,
+    pub active_peers: Gauge,
+    pub known_peers: Gauge,
+    pub diverged: Gauge,
+    pub stalled: Gauge,
+    pub blocks_behind: Gauge,
+    pub lagging: Gauge,
+    pub scrub_issues: Gauge,
+    pub votes: Family<VoteLabels, Counter>,
+    pub network_events: Family<EventLabels, Counter>,
+    pub gossip_messages: Family<TopicLabels, Counter>,
+    pub commits: Counter,
+    pub audit_write_seconds: Histogram,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        let mut metrics = Self {
+            registry: Registry::with_prefix("atlas"),
+            height: Gauge::default(),
+            pool_size: Gauge::default(),
+            active_peers: Gauge::default(),
+            known_peers: Gauge::default(),
+            diverged: Gauge::default(),
+            stalled: Gauge::default(),
+            blocks_behind: Gauge::default(),
+            lagging: Gauge::default(),
+            scrub_issues: Gauge::default(),
+            votes: Family::default(),
+            network_events: Family::default(),
+            gossip_messages: Family::default(),
+            commits: Counter::default(),
+            // 1ms .. ~4s
+            audit_write_seconds: Histogram::new(exponential_buckets(0.001, 2.0, 13)),
+        };
+
+        let registry = &mut metrics.registry;
+        registry.register("height", "Committed height (approved proposals)", metrics.height.clone());
+        registry.register("pool_size", "Proposals waiting in the consensus pool", metrics.pool_size.clone());
+        registry.register("active_peers", "Peers in the active set", metrics.active_peers.clone());
+        registry.register("known_peers", "Peers known to this node", metrics.known_peers.clone());
+        registry.register("diverged", "1 after a state root divergence was detected", metrics.diverged.clone());
+        registry.register("stalled", "1 while consensus is stalled", metrics.stalled.clone());
+        registry.register("blocks_behind", "Committed heights behind the median of the peers", metrics.blocks_behind.clone());
+        registry.register("lagging", "1 while the node is past the lag alarm threshold", metrics.lagging.clone());
+        registry.register("scrub_issues", "Discrepancies found by the last storage scrub", metrics.scrub_issues.clone());
+        registry.register("votes", "Signed votes recorded, by vote and source", metrics.votes.clone());
+        registry.register("network_events", "Events received from the P2P layer, by kind", metrics.network_events.clone());
+        registry.register("gossip_messages", "Gossip messages received, by topic", metrics.gossip_messages.clone());
+        registry.register("commits", "Proposals committed to storage", metrics.commits.clone());
+        registry.register("audit_write_seconds", "Time to write and fsync the audit file", metrics.audit_write_seconds.clone());
+
+        metrics
+    }
+}
+
+impl Metrics {
+    pub fn record_vote(&self, vote: &Vote, local: bool) {
+        let vote = match vote {
+            Vote::Yes => "yes",
+            Vote::No => "no",
+            Vote::Abstain => "abstain",
+        };
+        let source = if local { "local" } else { "peer" };
+        self.votes.get_or_create(&VoteLabels { vote, source }).inc();
+    }
+
+    pub fn record_network_event(&self, event: &AdapterEvent) {
+        let kind = match event {
+            AdapterEvent::PeerDiscovered(_) => "peer_discovered",
+            AdapterEvent::Heartbeat { .. } => "heartbeat",
+            AdapterEvent::Proposal(_) => "proposal",
+            AdapterEvent::PublishFailed { .. } => "publish_failed",
+            AdapterEvent::Gossip { .. } => "gossip",
+            AdapterEvent::Vote(_) => "vote",
+            AdapterEvent::ViewChange(_) => "view_change",
+            AdapterEvent::Misbehavior(_) => "misbehavior",
+            AdapterEvent::TxRequest { .. } => "tx_request",
+            AdapterEvent::TxBundle { .. } => "tx_bundle",
+        };
+        self.network_events.get_or_create(&EventLabels { kind }).inc();
+
+        let topic = match event {
+            AdapterEvent::Heartbeat { .. } => "atlas/heartbeat/v1",
+            AdapterEvent::Proposal(_) => "atlas/proposal/v1",
+            AdapterEvent::Vote(_) => "atlas/vote/v1",
+            AdapterEvent::ViewChange(_) => "atlas/view/v1",
+            AdapterEvent::Misbehavior(_) => "atlas/misbehavior/v1",
+            AdapterEvent::Gossip { topic, .. } => topic,
+            _ => return,
+        };
+        self.gossip_messages.get_or_create(&TopicLabels { topic: topic.to_string() }).inc();
+    }
+
+    pub fn record_audit_write(&self, elapsed: Duration) {
+        self.audit_write_seconds.observe(elapsed.as_secs_f64());
+    }
+
+    /// Prometheus text exposition of every metric.
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        // writing into a String cannot fail
+        let _ = encode(&mut out, &self.registry);
+        out
+    }
+}
+
+impl Cluster {
+    /// Copies cluster state into the gauges, right before a scrape.
+    pub async fn refresh_metrics(&self) {
+        let metrics = &self.metrics;
+        metrics.height.set(self.local_env.storage.read().await.state_root().height as i64);
+        metrics.pool_size.set(self.local_env.engine.lock().await.pool.all().len() as i64);
+        {
+            let peers = self.peer_manager.read().await;
+            metrics.active_peers.set(peers.active_peers.len() as i64);
+            metrics.known_peers.set(peers.known_peers.len() as i64);
+        }
+        metrics.diverged.set(self.diverged.load(Ordering::Relaxed) as i64);
+        metrics.stalled.set(self.stalled.load(Ordering::Relaxed) as i64);
+        metrics.blocks_behind.set(self.blocks_behind().await as i64);
+        metrics.lagging.set(self.lagging.load(Ordering::Relaxed) as i64);
+        let scrub_issues = self.last_scrub.lock().await.as_ref().map_or(0, |report| report.issues.len());
+        metrics.scrub_issues.set(scrub_issues as i64);
+    }
+}
+
+async fn serve_request(cluster: Arc<Cluster>, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    if request.uri().path() != "/metrics" {
+        return Ok(Response::builder().status(StatusCode::NOT_FOUND).body(Body::empty()).unwrap());
+    }
+
+    cluster.refresh_metrics().await;
+    Ok(Response::builder()
+        .header("content-type", "application/openmetrics-text; version=1.0.0; charset=utf-8")
+        .body(Body::from(cluster.metrics.encode()))
+        .unwrap())
+}
+
+/// Serves `/metrics` on `addr` until the task is aborted.
+pub async fn run_server(cluster: Arc<Cluster>, addr: SocketAddr) -> Result<(), hyper::Error> {
+    println!("[METRICS] Servidor escutando em {}", addr);
+
+    let make_service = make_service_fn(move |_| {
+        let cluster = Arc::clone(&cluster);
+        async move { Ok::<_, Infallible>(service_fn(move |req| serve_request(Arc::clone(&cluster), req))) }
+    });
+
+    Server::try_bind(&addr)?.serve(make_service).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atlas_sdk::utils::NodeId;
+
+    #[test]
+    fn test_encode_includes_labelled_counters() {
+        let metrics = Metrics::default();
+        metrics.record_vote(&Vote::Yes, true);
+        metrics.record_vote(&Vote::Yes, true);
+        metrics.record_network_event(&AdapterEvent::Vote(Vec::new()));
+        metrics.record_network_event(&AdapterEvent::PeerDiscovered(NodeId("peer".into())));
+        metrics.height.set(7);
+
+        let text = metrics.encode();
+        assert!(text.contains("atlas_height 7"), "{}", text);
+        assert!(text.contains(r#"atlas_votes_total{vote="yes",source="local"} 2"#), "{}", text);
+        assert!(text.contains(r#"atlas_network_events_total{kind="vote"} 1"#), "{}", text);
+        assert!(text.contains(r#"atlas_gossip_messages_total{topic="atlas/vote/v1"} 1"#), "{}", text);
+        assert_eq!(text.matches("atlas_gossip_messages_total{").count(), 1, "{}", text);
+        assert!(text.ends_with("# EOF\n"));
+    }
+}
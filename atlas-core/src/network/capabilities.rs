@@ -0,0 +1,133 @@
+//! capabilities.rs
+//!
+//! Optional services a node advertises to its peers.
+//!
+//! Capabilities travel in the identify agent string as a `caps=` token,
+//! each tagged with the version of the service it speaks, e.g.
+//! `caps=archive@1,relay@1`. Peers use them to pick suitable sync sources
+//! instead of probing nodes and waiting for timeouts. Unknown names are
+//! ignored so older nodes keep working when new capabilities appear.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// A service a node may offer to its peers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Capability {
+    /// Keeps the full proposal and result history.
+    Archive,
+
+    /// Serves state snapshots to syncing peers.
+    Snapshot,
+
+    /// Hands out test funds.
+    Faucet,
+
+    /// Relays traffic for peers behind NAT.
+    Relay,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 4] = [Capability::Archive, Capability::Snapshot, Capability::Faucet, Capability::Relay];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Capability::Archive => "archive",
+            Capability::Snapshot => "snapshot",
+            Capability::Faucet => "faucet",
+            Capability::Relay => "relay",
+        }
+    }
+
+    /// Current version of the service behind this capability.
+    pub fn version(&self) -> u32 {
+        1
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == name)
+    }
+}
+
+/// Set of capabilities with the service version advertised for each.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities(BTreeMap<Capability, u32>);
+
+impl Capabilities {
+    /// Parses a comma-separated list of names (e.g. from the CLI), at their
+    /// current versions. Unknown names are an error here.
+    pub fn from_names(list: &str) -> Result<Self, String> {
+        let mut caps = BTreeMap::new();
+        for name in list.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let cap = Capability::parse(name).ok_or_else(|| format!("unknown capability '{}'", name))?;
+            caps.insert(cap, cap.version());
+        }
+        Ok(Self(caps))
+    }
+
+    /// Extracts the `caps=` token from a peer's agent string.
+    pub fn from_agent(agent: &str) -> Self {
+        let Some(list) = agent.split_whitespace().find_map(|part| part.strip_prefix("caps=")) else {
+            return Self::default();
+        };
+
+        let caps = list
+            .split(',')
+            .filter_map(|entry| {
+                let (name, version) = entry.split_once('@')?;
+                Some((Capability::parse(name)?, version.parse().ok()?))
+            })
+            .collect();
+        Self(caps)
+    }
+
+    /// Returns `true` if `cap` is offered at `min_version` or later.
+    pub fn supports(&self, cap: Capability, min_version: u32) -> bool {
+        self.0.get(&cap).is_some_and(|v| *v >= min_version)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Capability names, in a stable order.
+    pub fn names(&self) -> Vec<String> {
+        self.0.keys().map(|c| c.as_str().to_string()).collect()
+    }
+}
+
+impl std::fmt::Display for Capabilities {
+    /// Wire form used inside the agent string: `archive@1,relay@1`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let entries: Vec<String> = self.0.iter().map(|(c, v)| format!("{}@{}", c.as_str(), v)).collect();
+        f.write_str(&entries.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_roundtrip_through_agent() {
+        let caps = Capabilities::from_names("relay, archive").unwrap();
+        let agent = format!("atlas-db/0.2.0 commit=abc proto=1 caps={}", caps);
+
+        let parsed = Capabilities::from_agent(&agent);
+        assert_eq!(parsed, caps);
+        assert!(parsed.supports(Capability::Archive, 1));
+        assert!(!parsed.supports(Capability::Archive, 2));
+        assert!(!parsed.supports(Capability::Snapshot, 1));
+    }
+
+    #[test]
+    fn test_unknown_capabilities() {
+        assert!(Capabilities::from_names("archive,teleport").is_err());
+
+        let parsed = Capabilities::from_agent("atlas-db/9.0 caps=teleport@3,snapshot@2");
+        assert!(parsed.supports(Capability::Snapshot, 2));
+        assert_eq!(parsed.names(), vec!["snapshot".to_string()]);
+        assert!(Capabilities::from_agent("rust-libp2p/0.56").is_empty());
+    }
+}
@@ -1,4 +1,5 @@
-use libp2p::identity;
+use atlas_sdk::utils::NodeId;
+use libp2p::{identity, PeerId};
 use std::fs;
 use std::io::{self, Read, Write};
 use std::path::Path;
@@ -23,7 +24,7 @@ pub fn load_or_generate_keypair(path: &Path) -> io::Result<identity::Keypair> {
         let keypair = identity::Keypair::generate_ed25519();
         let bytes = keypair
             .to_protobuf_encoding()
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            .map_err(io::Error::other)?;
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
@@ -31,4 +32,49 @@ pub fn load_or_generate_keypair(path: &Path) -> io::Result<identity::Keypair> {
         file.write_all(&bytes)?;
         Ok(keypair)
     }
-}
\ No newline at end of file
+}
+/// Returns the node ID (libp2p `PeerId`) identified by an ed25519 public key.
+///
+/// Node IDs are derived from the node's keypair, so this is what binds a
+/// `NodeId` to the key that signs on its behalf.
+pub fn node_id_for_key(public_key: &[u8]) -> Option<NodeId> {
+    let key = identity::ed25519::PublicKey::try_from_bytes(public_key).ok()?;
+    Some(NodeId(identity::PublicKey::from(key).to_peer_id().to_string()))
+}
+
+/// Returns `true` if `public_key` is the key `node` was derived from.
+pub fn key_matches_node(node: &NodeId, public_key: &[u8]) -> bool {
+    node_id_for_key(public_key).as_ref() == Some(node)
+}
+
+/// Extracts the ed25519 public key embedded in a node ID.
+///
+/// ed25519 peer IDs inline the key instead of hashing it, so the trusted
+/// key of any validator can be recovered from its ID alone.
+pub fn node_public_key(node: &NodeId) -> Option<Vec<u8>> {
+    let peer_id: PeerId = node.0.parse().ok()?;
+    let multihash = peer_id.as_ref();
+    if multihash.code() != 0 {
+        return None;
+    }
+    let key = identity::PublicKey::try_decode_protobuf(multihash.digest()).ok()?;
+    Some(key.try_into_ed25519().ok()?.to_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_id_binds_to_its_key() {
+        let keypair = identity::Keypair::generate_ed25519();
+        let public_key = keypair.public().try_into_ed25519().unwrap().to_bytes();
+        let node = NodeId(keypair.public().to_peer_id().to_string());
+
+        assert_eq!(node_id_for_key(&public_key), Some(node.clone()));
+        assert!(key_matches_node(&node, &public_key));
+        assert!(!key_matches_node(&NodeId("node-1".into()), &public_key));
+        assert_eq!(node_public_key(&node), Some(public_key.to_vec()));
+        assert_eq!(node_public_key(&NodeId("node-1".into())), None);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod capabilities;
 pub mod error;
 pub mod p2p;
-pub mod key_manager;
\ No newline at end of file
+pub mod key_manager;
+pub mod namespace;
\ No newline at end of file
@@ -0,0 +1,191 @@
+//! namespace.rs
+//!
+//! Named networks (`--network <name>`).
+//!
+//! A node started with a network name keeps its data directory, keypair,
+//! default ports and gossipsub topics apart from nodes of other networks, so
+//! several networks can run side by side on the same host without sharing
+//! state or exchanging messages. Without a name the node keeps the legacy,
+//! un-namespaced layout.
+
+use std::path::PathBuf;
+
+use crate::error::{AtlasError, Result};
+
+/// Maximum length of a network name.
+pub const MAX_NETWORK_NAME_LEN: usize = 32;
+
+/// Root directory that holds the per-network data directories.
+pub const NETWORKS_DIR: &str = "data";
+
+/// Default gRPC port of the legacy (unnamed) network.
+pub const BASE_GRPC_PORT: u16 = 50051;
+
+/// Default P2P port of the first named network.
+pub const BASE_P2P_PORT: u16 = 4001;
+
+/// Distance between the default ports of two networks.
+const PORT_STRIDE: u16 = 100;
+
+/// Well-known networks and their fixed port slots.
+const KNOWN_NETWORKS: &[&str] = &["mainnet", "testnet", "devnet"];
+
+/// Number of port slots available to networks outside `KNOWN_NETWORKS`.
+const CUSTOM_PORT_SLOTS: u16 = 90;
+
+/// Identifies which network a node belongs to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkNamespace {
+    name: Option<String>,
+}
+
+impl NetworkNamespace {
+    /// Builds a named network, validating the name.
+    ///
+    /// Names are limited to lowercase ASCII letters, digits and `-` because
+    /// they end up in file paths and topic names.
+    pub fn named(name: &str) -> Result<Self> {
+        let valid = !name.is_empty()
+            && name.len() <= MAX_NETWORK_NAME_LEN
+            && name.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+
+        if !valid {
+            return Err(AtlasError::Config(format!(
+                "invalid network name '{}': expected 1-{} characters of [a-z0-9-]",
+                name, MAX_NETWORK_NAME_LEN
+            )));
+        }
+
+        Ok(Self { name: Some(name.to_string()) })
+    }
+
+    /// Builds the namespace from an optional `--network` value.
+    pub fn from_arg(arg: Option<&str>) -> Result<Self> {
+        arg.map(Self::named).transpose().map(Option::unwrap_or_default)
+    }
+
+    /// Network name, or `None` for the legacy network.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Directory holding this network's config, keys and logs.
+    pub fn data_dir(&self) -> PathBuf {
+        match &self.name {
+            Some(name) => PathBuf::from(NETWORKS_DIR).join(name),
+            None => PathBuf::from("."),
+        }
+    }
+
+    /// Resolves `relative` inside the network data directory.
+    pub fn path(&self, relative: &str) -> PathBuf {
+        match &self.name {
+            Some(_) => self.data_dir().join(relative),
+            None => PathBuf::from(relative),
+        }
+    }
+
+    /// Default gRPC port for this network.
+    pub fn default_grpc_port(&self) -> u16 {
+        BASE_GRPC_PORT + self.port_offset()
+    }
+
+    /// Default P2P listen address for this network.
+    ///
+    /// The legacy network keeps listening on a random port.
+    pub fn default_listen_addr(&self) -> String {
+        match &self.name {
+            Some(_) => format!("/ip4/0.0.0.0/tcp/{}", BASE_P2P_PORT + self.port_offset()),
+            None => "/ip4/0.0.0.0/tcp/0".to_string(),
+        }
+    }
+
+    /// Maps a logical topic (e.g. `atlas/vote/v1`) to the topic used on the wire.
+    pub fn wire_topic(&self, topic: &str) -> String {
+        match (&self.name, topic.strip_prefix("atlas/")) {
+            (Some(name), Some(rest)) => format!("atlas/{}/{}", name, rest),
+            _ => topic.to_string(),
+        }
+    }
+
+    /// Inverse of [`wire_topic`](Self::wire_topic).
+    pub fn logical_topic<'a>(&self, wire: &'a str) -> std::borrow::Cow<'a, str> {
+        let stripped = self.name.as_ref().and_then(|name| {
+            wire.strip_prefix("atlas/")
+                .and_then(|rest| rest.strip_prefix(name.as_str()))
+                .and_then(|rest| rest.strip_prefix('/'))
+        });
+
+        match stripped {
+            Some(rest) => format!("atlas/{}", rest).into(),
+            None => wire.into(),
+        }
+    }
+
+    /// Identify protocol version advertised to peers.
+    pub fn protocol_version(&self) -> String {
+        match &self.name {
+            Some(name) => format!("atlas/{}/1.0", name),
+            None => "atlas/1.0".to_string(),
+        }
+    }
+
+    fn port_offset(&self) -> u16 {
+        let Some(name) = &self.name else { return 0 };
+
+        let slot = match KNOWN_NETWORKS.iter().position(|n| n == name) {
+            Some(i) => i as u16,
+            None => {
+                // FNV-1a: stable across runs and platforms.
+                let hash = name.bytes().fold(0x811c9dc5u32, |h, b| (h ^ b as u32).wrapping_mul(0x01000193));
+                KNOWN_NETWORKS.len() as u16 + (hash % CUSTOM_PORT_SLOTS as u32) as u16
+            }
+        };
+
+        slot * PORT_STRIDE
+    }
+}
+
+impl std::fmt::Display for NetworkNamespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name.as_deref().unwrap_or("default"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_network_is_unchanged() {
+        let ns = NetworkNamespace::from_arg(None).unwrap();
+        assert_eq!(ns.path("config.json"), PathBuf::from("config.json"));
+        assert_eq!(ns.default_grpc_port(), BASE_GRPC_PORT);
+        assert_eq!(ns.default_listen_addr(), "/ip4/0.0.0.0/tcp/0");
+        assert_eq!(ns.wire_topic("atlas/vote/v1"), "atlas/vote/v1");
+        assert_eq!(ns.protocol_version(), "atlas/1.0");
+    }
+
+    #[test]
+    fn test_named_network_is_isolated() {
+        let test = NetworkNamespace::named("testnet").unwrap();
+        let dev = NetworkNamespace::named("devnet").unwrap();
+
+        assert_eq!(test.path("keys/keypair"), PathBuf::from("data/testnet/keys/keypair"));
+        assert_ne!(test.default_grpc_port(), dev.default_grpc_port());
+        assert_ne!(test.default_listen_addr(), dev.default_listen_addr());
+
+        let wire = test.wire_topic("atlas/proposal/v1");
+        assert_eq!(wire, "atlas/testnet/proposal/v1");
+        assert_eq!(test.logical_topic(&wire), "atlas/proposal/v1");
+        assert_eq!(dev.logical_topic(&wire), wire.as_str());
+    }
+
+    #[test]
+    fn test_rejects_bad_names() {
+        assert!(NetworkNamespace::named("").is_err());
+        assert!(NetworkNamespace::named("../etc").is_err());
+        assert!(NetworkNamespace::named("Main").is_err());
+        assert!(NetworkNamespace::named(&"a".repeat(MAX_NETWORK_NAME_LEN + 1)).is_err());
+    }
+}
@@ -16,8 +16,10 @@ use crate::network::p2p::{
 use super::{
     behaviour::P2pBehaviour as Behaviour,
     config::P2pConfig,
+    envelope,
     events::{AdapterEvent, ComposedEvent},
     error::P2pError,
+    limiter::{ServeLimiter, MAX_INFLIGHT_PER_PEER},
 };
 
 use libp2p::{
@@ -36,6 +38,7 @@ use libp2p::{
         Behaviour as RequestResponseBehaviour, 
         Config as RequestResponseConfig, 
         Event as RequestResponseEvent, 
+        InboundRequestId,
         Message,
         OutboundRequestId as RequestId, 
         ProtocolSupport
@@ -55,8 +58,14 @@ use libp2p::{
 use tokio::sync::{mpsc, RwLock};
 
 use crate::network::key_manager;
+use crate::network::namespace::NetworkNamespace;
+use crate::network::capabilities::Capabilities;
+use crate::version::{self, UpgradeAdvisory};
 use std::path::Path;
 
+/// Intervalo entre heartbeats publicados por cada nó.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(3);
+
 pub struct Libp2pAdapter {
     pub peer_id: PeerId,
     pub swarm: Swarm<Behaviour>,
@@ -66,11 +75,16 @@ pub struct Libp2pAdapter {
     addr_book: HashMap<NodeId, HashSet<Multiaddr>>,
     dial_backoff: HashMap<NodeId, Instant>,
     last_kad_bootstrap: std::time::Instant,   
+    network: NetworkNamespace,
+    upgrades: UpgradeAdvisory,
+    serving: ServeLimiter<InboundRequestId>,
+    heartbeat_payload: Vec<u8>,
 }
 
 pub enum AdapterCmd {
     Publish { topic: String, data: Vec<u8> },
     RequestTxs { peer: libp2p::PeerId, req: TxRequest },
+    SetHeartbeat(Vec<u8>),
     Shutdown,
 }
 
@@ -105,8 +119,8 @@ impl Libp2pAdapter {
 
         // identify
         let identify = identify::Behaviour::new(
-            identify::Config::new("atlas/1.0".into(), key.public())
-                .with_agent_version("rust-libp2p".into())
+            identify::Config::new(cfg.network.protocol_version(), key.public())
+                .with_agent_version(agent_string(&cfg))
         );
 
         // mdns
@@ -116,15 +130,23 @@ impl Libp2pAdapter {
         )?;
 
         // kad
-        let mut kad_cfg = kad::Config::default();
+        // redes nomeadas usam um protocolo Kademlia próprio para não misturar DHTs
+        let mut kad_cfg = match cfg.network.name() {
+            Some(name) => kad::Config::new(
+                StreamProtocol::try_from_owned(format!("/atlas/{}/kad/1.0.0", name))
+                    .expect("nome de rede já validado"),
+            ),
+            None => kad::Config::default(),
+        };
         kad_cfg.set_query_timeout(std::time::Duration::from_secs(5));
         let store = kad::store::MemoryStore::new(peer_id);
         let kad = kad::Behaviour::with_config(peer_id, store, kad_cfg);
 
         // request-response
         let rr = {
-            let mut cfg = RequestResponseConfig::default();
-            cfg.set_request_timeout(std::time::Duration::from_secs(3));
+            let cfg = RequestResponseConfig::default()
+                .with_request_timeout(std::time::Duration::from_secs(3))
+                .with_max_concurrent_streams(MAX_INFLIGHT_PER_PEER);
         
             let protocols = std::iter::once((
                 StreamProtocol::new("/atlas/tx/1"),
@@ -147,7 +169,7 @@ impl Libp2pAdapter {
         };
 
         // tópicos
-        behaviour.subscribe_core_topics()?; // usa P2pError::Gossipsub
+        behaviour.subscribe_core_topics(&cfg.network)?; // usa P2pError::Gossipsub
 
         // swarm
         let mut swarm = Swarm::new(transport, behaviour, peer_id, SwarmConfig::with_tokio_executor());
@@ -168,14 +190,16 @@ impl Libp2pAdapter {
         let dial_backoff = HashMap::new();
         let last_kad_bootstrap = std::time::Instant::now();
 
-        Ok(Self { peer_id, swarm, evt_tx, cmd_rx, peer_mgr, addr_book, dial_backoff, last_kad_bootstrap })
+        let network = cfg.network.clone();
+
+        Ok(Self { peer_id, swarm, evt_tx, cmd_rx, peer_mgr, addr_book, dial_backoff, last_kad_bootstrap, network, upgrades: UpgradeAdvisory::default(), serving: ServeLimiter::default(), heartbeat_payload: b"hi from adapter".to_vec() })
     }
 
     /// Loop principal: processa eventos do Swarm e repassa ao Cluster
     pub async fn run(mut self) {
         use futures::StreamExt;
         let mut maintain = tokio::time::interval(Duration::from_secs(10));
-        let mut heartbeat_interval = tokio::time::interval(Duration::from_secs(3));
+        let mut heartbeat_interval = tokio::time::interval(HEARTBEAT_INTERVAL);
         
     
         loop {
@@ -183,35 +207,40 @@ impl Libp2pAdapter {
                 // 1) eventos do swarm
                 swarm_ev = self.swarm.select_next_some() => {
                     match swarm_ev {
-                        SwarmEvent::Behaviour(ComposedEvent::Identify(ev)) => {
-                            if let libp2p::identify::Event::Received { peer_id, info, .. } = ev {
-                                let id = peer_id.to_string().into();
-                                for addr in info.listen_addrs {
-                                    self.learn_addr(&id, addr.clone());
-                                    self.swarm.behaviour_mut().kad.add_address(&peer_id, addr);
-                                }
-                                // toque o peer (marca last_seen = agora)
-                                self.touch_peer(id).await;
+                        SwarmEvent::Behaviour(ComposedEvent::Identify(libp2p::identify::Event::Received { peer_id, info, .. })) => {
+                            let id: NodeId = peer_id.to_string().into();
+                            if let Some(newer) = self.upgrades.observe(id.clone(), &info.agent_version) {
+                                tracing::warn!(
+                                    "⚠️ ATUALIZAÇÃO NECESSÁRIA: a maioria dos peers ({} conhecidos) já usa o protocolo v{} (este nó: v{}, {})",
+                                    self.upgrades.known_peers(), newer, version::PROTOCOL_VERSION, version::agent_version()
+                                );
+                                tracing::info!(target: "consensus", "EVENT:UPGRADE_ADVISORY local={} peers={}", version::PROTOCOL_VERSION, newer);
+                            }
+                            let caps = Capabilities::from_agent(&info.agent_version);
+                            self.peer_mgr.write().await.handle_command(PeerCommand::Capabilities(id.clone(), caps));
+                            for addr in info.listen_addrs {
+                                self.learn_addr(&id, addr.clone());
+                                self.swarm.behaviour_mut().kad.add_address(&peer_id, addr);
+                            }
+                            // toque o peer (marca last_seen = agora)
+                            self.touch_peer(id).await;
                             
-                                if self.last_kad_bootstrap.elapsed() >= Duration::from_secs(60) {
-                                    let _ = self.swarm.behaviour_mut().kad.bootstrap();
-                                    self.last_kad_bootstrap = std::time::Instant::now();
-                                }
+                            if self.last_kad_bootstrap.elapsed() >= Duration::from_secs(60) {
+                                let _ = self.swarm.behaviour_mut().kad.bootstrap();
+                                self.last_kad_bootstrap = std::time::Instant::now();
                             }
                         }
     
-                        SwarmEvent::Behaviour(ComposedEvent::Ping(ev)) => {
-                            if let libp2p::ping::Event { peer, result: Ok(rtt), .. } = ev {
-                                let id: NodeId = peer.to_string().into();
-                                // atualiza latência e last_seen
-                                let mut peer_mgr = self.peer_mgr.write().await;
-                                let mut n = peer_mgr
-                                    .get_peer_stats(&id)
-                                    .unwrap_or_else(|| Node::placeholder());
-                                n.update_latency(Some(rtt.as_millis() as u64));
-                                n.update_last_seen();
-                                let _ = peer_mgr.handle_command(PeerCommand::UpdateStats(id, n));
-                            }
+                        SwarmEvent::Behaviour(ComposedEvent::Ping(libp2p::ping::Event { peer, result: Ok(rtt), .. })) => {
+                            let id: NodeId = peer.to_string().into();
+                            // atualiza latência e last_seen
+                            let mut peer_mgr = self.peer_mgr.write().await;
+                            let mut n = peer_mgr
+                                .get_peer_stats(&id)
+                                .unwrap_or_else(Node::placeholder);
+                            n.update_latency(Some(rtt.as_millis() as u64));
+                            n.update_last_seen();
+                            let _ = peer_mgr.handle_command(PeerCommand::UpdateStats(id, n));
                         }
     
                         #[cfg(feature = "mdns")]
@@ -225,9 +254,7 @@ impl Libp2pAdapter {
                                         let node = Node { reliability_score: 0.0, latency: None, ..Default::default() };
                                         self.peer_mgr.write().await.handle_command(PeerCommand::Register(id.clone(), node));
                                         let _ = Swarm::dial(&mut self.swarm, addr);
-                                        if let Ok(_) = self.evt_tx.send(AdapterEvent::PeerDiscovered(peer.to_string().into())).await {
-                                            // Handle error if necessary
-                                        }
+                                        let _ = self.evt_tx.send(AdapterEvent::PeerDiscovered(peer.to_string().into())).await;
                                     }
                                 }
                                 libp2p::mdns::Event::Expired(list) => {
@@ -243,34 +270,44 @@ impl Libp2pAdapter {
                             }
                         }
     
-                        SwarmEvent::Behaviour(ComposedEvent::Kad(ev)) => {
-                            if let kad::Event::RoutingUpdated { peer, addresses, .. } = ev {
-                                let id: NodeId = peer.to_string().into();
-                                for addr in addresses.into_vec() {
-                                    self.learn_addr(&id, addr.clone());
-                                    let _ = Swarm::dial(&mut self.swarm, addr);
-                                }
-                                if let Ok(_) = self.evt_tx.send(AdapterEvent::PeerDiscovered(peer.to_string().into())).await {
-                                    // Handle error if necessary
-                                }
+                        SwarmEvent::Behaviour(ComposedEvent::Kad(kad::Event::RoutingUpdated { peer, addresses, .. })) => {
+                            let id: NodeId = peer.to_string().into();
+                            for addr in addresses.into_vec() {
+                                self.learn_addr(&id, addr.clone());
+                                let _ = Swarm::dial(&mut self.swarm, addr);
                             }
+                            let _ = self.evt_tx.send(AdapterEvent::PeerDiscovered(peer.to_string().into())).await;
                         }
     
                         SwarmEvent::Behaviour(ComposedEvent::Gossipsub(ev)) => {
                             match ev {
                                 GossipsubEvent::Message { propagation_source, message, .. } => {
-                                    let topic = message.topic.as_str();
-                                    let data = message.data.clone();
+                                    #[cfg(feature = "fault-injection")]
+                                    if crate::fault::faults().drop_gossip() {
+                                        tracing::debug!("🧨 gossip descartado (falha injetada) topic={}", message.topic);
+                                        continue;
+                                    }
+
+                                    let topic = self.network.logical_topic(message.topic.as_str());
+                                    let data = match envelope::open(&message.data) {
+                                        Ok((_, payload)) => payload.to_vec(),
+                                        Err(e) => {
+                                            tracing::warn!("gossip descartado topic={}: {e}", message.topic);
+                                            continue;
+                                        }
+                                    };
                                     let from = message.source.unwrap_or(propagation_source);
                                     tracing::info!("RX gossipsub topic={} size={} from={}", topic, data.len(), from);
 
-                                    let event = match topic {
+                                    let event = match topic.as_ref() {
                                         "atlas/heartbeat/v1" => AdapterEvent::Heartbeat {
                                             from: from.to_string().into(),
                                             data,
                                         },
                                         "atlas/proposal/v1" => AdapterEvent::Proposal(data),
                                         "atlas/vote/v1" => AdapterEvent::Vote(data),
+                                        "atlas/view/v1" => AdapterEvent::ViewChange(data),
+                                        "atlas/misbehavior/v1" => AdapterEvent::Misbehavior(data),
                                         _ => AdapterEvent::Gossip {
                                             topic: topic.to_string(),
                                             from: from.to_string().into(),
@@ -295,10 +332,17 @@ impl Libp2pAdapter {
     
                         SwarmEvent::Behaviour(ComposedEvent::ReqRes(ev)) => match ev {
                             RequestResponseEvent::Message { peer, message, .. } => match message {
-                                Message::Request { request, channel, .. } => {
+                                Message::Request { request_id, request, channel } => {
                                     // atividade do peer
                                     let id: NodeId = peer.to_string().into();
-                                    self.touch_peer(id).await;
+                                    self.touch_peer(id.clone()).await;
+
+                                    // acima do limite: recusa na hora (o canal é descartado) para
+                                    // não competir com o tráfego de consenso
+                                    if !self.serving.try_acquire(&id, request_id) {
+                                        tracing::debug!("limite de atendimento atingido; recusando requisição de {peer} ({} em andamento)", self.serving.inflight());
+                                        continue;
+                                    }
                                     let _ = (request, channel);
                                     // self.swarm.behaviour_mut().rr.send_response(channel, resp)?;
                                 }
@@ -314,16 +358,16 @@ impl Libp2pAdapter {
                                 let id: NodeId = peer.to_string().into();
                                 self.touch_peer(id).await;
                             }
-                            RequestResponseEvent::InboundFailure { peer, .. } => {
+                            RequestResponseEvent::InboundFailure { peer, request_id, .. } => {
+                                self.serving.release(&request_id);
                                 let id: NodeId = peer.to_string().into();
                                 self.touch_peer(id).await;
                             }
-                            RequestResponseEvent::ResponseSent { peer, .. } => {
+                            RequestResponseEvent::ResponseSent { peer, request_id, .. } => {
+                                self.serving.release(&request_id);
                                 let id: NodeId = peer.to_string().into();
                                 self.touch_peer(id).await;
                             }
-                        
-                            _ => {}
                         },
                         
                         SwarmEvent::ConnectionEstablished { peer_id, .. } => {
@@ -351,8 +395,8 @@ impl Libp2pAdapter {
     
                 // 2) manutenção (braço separado!)
                 _ = heartbeat_interval.tick() => {
-                    let topic = IdentTopic::new("atlas/heartbeat/v1");
-                    let data = b"hi from adapter".to_vec();
+                    let topic = IdentTopic::new(self.network.wire_topic("atlas/heartbeat/v1"));
+                    let data = envelope::seal(self.heartbeat_payload.clone());
                     println!("💓 heartbeat");
                     if let Err(e) = self.swarm.behaviour_mut().gossipsub.publish(topic, data) {
                         tracing::warn!("Failed to publish heartbeat: {e}");
@@ -387,22 +431,23 @@ impl Libp2pAdapter {
                 cmd = self.cmd_rx.recv() => {
                     match cmd {
                         Some(AdapterCmd::Publish { topic, data }) => {
-                            let t = IdentTopic::new(&topic);
-                            match self.swarm.behaviour_mut().gossipsub.publish(t.clone(), data.clone()) {
+                            let t = IdentTopic::new(self.network.wire_topic(&topic));
+                            match self.swarm.behaviour_mut().gossipsub.publish(t.clone(), envelope::seal(data.clone())) {
                                 Ok(id) => {
                                     tracing::info!("TX gossipsub ok topic={} id={id}", t.hash().to_string());
                                 }
                                 Err(e) => {
                                     tracing::warn!("TX gossipsub FAIL topic={} err={e}", t.hash().to_string());
-                                    if let Ok(_) = self.evt_tx.send(AdapterEvent::PublishFailed { topic: t.to_string(), data }).await {
-                                        // Handle error if necessary
-                                    }
+                                    let _ = self.evt_tx.send(AdapterEvent::PublishFailed { topic, data }).await;
                                 }
                             }
                         }
                         Some(AdapterCmd::RequestTxs { peer, req }) => {
                             let _ = self.swarm.behaviour_mut().rr.send_request(&peer, req);
                         }
+                        Some(AdapterCmd::SetHeartbeat(data)) => {
+                            self.heartbeat_payload = data;
+                        }
                         Some(AdapterCmd::Shutdown) | None => break,
                     }
                 }
@@ -414,7 +459,7 @@ impl Libp2pAdapter {
         let mut peer_mgr = self.peer_mgr.write().await;
         let mut n = peer_mgr
             .get_peer_stats(&id)
-            .unwrap_or_else(|| Node::placeholder());
+            .unwrap_or_else(Node::placeholder);
         n.update_last_seen();
         let _ = peer_mgr.handle_command(PeerCommand::UpdateStats(id, n));
     }
@@ -422,8 +467,8 @@ impl Libp2pAdapter {
 
     // helpers p/ publicar e request/response
     pub fn publish(&mut self, topic: &str, bytes: Vec<u8>) {
-        let t = IdentTopic::new(topic);
-        let _ = self.swarm.behaviour_mut().gossipsub.publish(t, bytes);
+        let t = IdentTopic::new(self.network.wire_topic(topic));
+        let _ = self.swarm.behaviour_mut().gossipsub.publish(t, envelope::seal(bytes));
     }
 
     pub fn request_txs(&mut self, peer: libp2p::PeerId, req: TxRequest) -> RequestId {
@@ -448,3 +493,12 @@ impl Libp2pAdapter {
         }
     }
 }
+
+/// Agent anunciado via identify: versão da build mais os serviços oferecidos.
+fn agent_string(cfg: &P2pConfig) -> String {
+    if cfg.capabilities.is_empty() {
+        version::agent_version()
+    } else {
+        format!("{} caps={}", version::agent_version(), cfg.capabilities)
+    }
+}
@@ -11,6 +11,7 @@ use super::{
     codec::TxCodec,
     error::P2pError,
 };
+use crate::network::namespace::NetworkNamespace;
 
 // DICA: ajuste o caminho do ComposedEvent conforme seu layout real.
 // Se o módulo é "events.rs" no mesmo nível deste arquivo, use `super::events::ComposedEvent`.
@@ -28,13 +29,15 @@ pub struct P2pBehaviour {
 }
 
 impl P2pBehaviour {
-    pub fn subscribe_core_topics(&mut self) -> Result<(), P2pError> {
+    pub fn subscribe_core_topics(&mut self, network: &NetworkNamespace) -> Result<(), P2pError> {
         use libp2p::gossipsub::IdentTopic;
 
         let topics = [
-            IdentTopic::new("atlas/heartbeat/v1"),
-            IdentTopic::new("atlas/proposal/v1"),
-            IdentTopic::new("atlas/vote/v1"),
+            IdentTopic::new(network.wire_topic("atlas/heartbeat/v1")),
+            IdentTopic::new(network.wire_topic("atlas/proposal/v1")),
+            IdentTopic::new(network.wire_topic("atlas/vote/v1")),
+            IdentTopic::new(network.wire_topic("atlas/view/v1")),
+            IdentTopic::new(network.wire_topic("atlas/misbehavior/v1")),
         ];
 
         for t in topics {
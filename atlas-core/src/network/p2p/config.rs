@@ -1,3 +1,5 @@
+use crate::network::{capabilities::Capabilities, namespace::NetworkNamespace};
+
 #[derive(Clone, Debug)]
 pub struct P2pConfig {
     pub listen_multiaddrs: Vec<String>, // e.g. ["/ip4/0.0.0.0/tcp/4001"]
@@ -5,4 +7,6 @@ pub struct P2pConfig {
     pub enable_mdns: bool,
     pub enable_kademlia: bool,
     pub keypair_path: String,
+    pub network: NetworkNamespace,      // rede nomeada (--network); define os tópicos
+    pub capabilities: Capabilities,     // serviços anunciados via identify
 }
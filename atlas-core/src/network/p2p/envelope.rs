@@ -0,0 +1,145 @@
+//! envelope.rs
+//!
+//! Versioned framing for gossip payloads.
+//!
+//! Every payload published by this build is prefixed with an eight-byte
+//! magic and a wire version. Receivers strip the envelope before decoding, reject
+//! versions they do not know, and pass un-enveloped payloads from older
+//! builds through unchanged, so a mixed-version network keeps talking while
+//! an upgrade rolls out.
+
+use atlas_sdk::{env::proposal::{Proposal, SealedProposal}, utils::NodeId};
+use serde::Deserialize;
+
+/// Marks a payload as enveloped.
+///
+/// Legacy bincode payloads start with a little-endian `u64`: a string length
+/// (proposals, votes) or a height (heartbeats). Its last byte is zero for
+/// any value below 2^56, so a magic ending in `0xFF` cannot match them. The
+/// oldest heartbeats were plain text and start with `h`.
+pub const ENVELOPE_MAGIC: [u8; 8] = [0xA7, 0x1A, b'A', b'T', b'L', b'A', b'S', 0xFF];
+
+/// Wire version written by this build.
+pub const WIRE_VERSION: u8 = 1;
+
+/// Version reported for payloads sent without an envelope.
+pub const LEGACY_WIRE_VERSION: u8 = 0;
+
+/// Wraps `payload` in an envelope carrying [`WIRE_VERSION`].
+pub fn seal(payload: Vec<u8>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len() + ENVELOPE_MAGIC.len() + 1);
+    out.extend_from_slice(&ENVELOPE_MAGIC);
+    out.push(WIRE_VERSION);
+    out.extend(payload);
+    out
+}
+
+/// Strips the envelope, returning the wire version and the payload.
+///
+/// Fails for versions newer than [`WIRE_VERSION`].
+pub fn open(bytes: &[u8]) -> Result<(u8, &[u8]), String> {
+    match bytes.strip_prefix(&ENVELOPE_MAGIC[..]) {
+        Some([version, payload @ ..]) => {
+            if *version > WIRE_VERSION {
+                return Err(format!("unsupported wire version {} (max {})", version, WIRE_VERSION));
+            }
+            Ok((*version, payload))
+        }
+        Some([]) => Err("envelope without a wire version".to_string()),
+        None => Ok((LEGACY_WIRE_VERSION, bytes)),
+    }
+}
+
+/// Proposal layout of builds from before `Proposal::request_id`.
+#[derive(Deserialize)]
+struct LegacyProposal {
+    id: String,
+    proposer: NodeId,
+    content: String,
+    parent: Option<String>,
+    #[serde(with = "hex::serde")]
+    signature: [u8; 64],
+    public_key: Vec<u8>,
+}
+
+/// Decodes a proposal payload and seals it, keeping `payload` as its wire bytes.
+pub fn decode_sealed(payload: Vec<u8>) -> Result<SealedProposal, String> {
+    let proposal = decode_proposal(&payload)?;
+    Ok(SealedProposal::from_wire(proposal, payload))
+}
+
+/// Decodes a proposal payload, accepting the layout without `request_id`.
+pub fn decode_proposal(payload: &[u8]) -> Result<Proposal, String> {
+    let current_err = match bincode::deserialize::<Proposal>(payload) {
+        Ok(proposal) => return Ok(proposal),
+        Err(e) => e,
+    };
+
+    let legacy: LegacyProposal = bincode::deserialize(payload)
+        .map_err(|_| format!("decode proposal: {current_err}"))?;
+
+    Ok(Proposal {
+        id: legacy.id,
+        proposer: legacy.proposer,
+        content: legacy.content,
+        parent: legacy.parent,
+        signature: legacy.signature,
+        public_key: legacy.public_key,
+        request_id: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{env::storage::StateRoot, network::p2p::protocol::Heartbeat};
+
+    /// Proposal as encoded by builds from before `request_id`: id "prop-1",
+    /// proposer "node-a", content "{}", no parent, signature 0x07 x 64 (as a
+    /// hex string) and public key [1, 2].
+    const BASELINE_PROPOSAL: &str = concat!(
+        "060000000000000070726f702d31",
+        "06000000000000006e6f64652d61",
+        "02000000000000007b7d",
+        "00",
+        "8000000000000000",
+        "3037303730373037303730373037303730373037303730373037303730373037",
+        "3037303730373037303730373037303730373037303730373037303730373037",
+        "3037303730373037303730373037303730373037303730373037303730373037",
+        "3037303730373037303730373037303730373037303730373037303730373037",
+        "02000000000000000102",
+    );
+
+    #[test]
+    fn test_envelope_round_trip_and_legacy_passthrough() {
+        let sealed = seal(vec![1, 2, 3]);
+        assert_eq!(open(&sealed).unwrap(), (WIRE_VERSION, &[1u8, 2, 3][..]));
+        assert_eq!(open(&[5, 0, 0]).unwrap(), (LEGACY_WIRE_VERSION, &[5u8, 0, 0][..]));
+
+        let mut future = sealed.clone();
+        future[ENVELOPE_MAGIC.len()] = WIRE_VERSION + 1;
+        assert!(open(&future).is_err());
+    }
+
+    #[test]
+    fn test_legacy_heartbeats_are_never_mistaken_for_envelopes() {
+        // Altura cujos bytes baixos batiam com a magia antiga de dois bytes.
+        let heartbeat = bincode::serialize(&Heartbeat { state: StateRoot { height: 0x1AA7, root: [0u8; 32] } }).unwrap();
+        assert_eq!(open(&heartbeat).unwrap(), (LEGACY_WIRE_VERSION, &heartbeat[..]));
+        assert_eq!(open(b"hi from adapter").unwrap().0, LEGACY_WIRE_VERSION);
+    }
+
+    #[test]
+    fn test_decode_baseline_proposal() {
+        let old = hex::decode(BASELINE_PROPOSAL).unwrap();
+        let proposal = decode_proposal(&old).unwrap();
+        assert_eq!(proposal.id, "prop-1");
+        assert_eq!(proposal.proposer, NodeId("node-a".into()));
+        assert_eq!(proposal.signature, [7u8; 64]);
+        assert_eq!(proposal.public_key, vec![1, 2]);
+        assert_eq!(proposal.request_id, None);
+
+        let current = decode_proposal(&proposal.bytes()).unwrap();
+        assert_eq!(current.id, proposal.id);
+    }
+}
@@ -4,7 +4,6 @@ use libp2p::{
     kad,
     request_response,
     ping,
-    PeerId,
 };
 
 use atlas_sdk::utils::NodeId;
@@ -13,6 +12,7 @@ use crate::network::p2p::protocol::{TxRequest, TxBundle};
 
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum ComposedEvent {
     Identify(IdentifyEvent),
     Ping(ping::Event),
@@ -46,6 +46,8 @@ pub enum AdapterEvent {
     PublishFailed {topic: String, data: Vec<u8>},
     Gossip {topic: String, data: Vec<u8>, from: NodeId},
     Vote(Vec<u8>),
+    ViewChange(Vec<u8>),
+    Misbehavior(Vec<u8>),
     TxRequest { from: NodeId, txids: Vec<[u8;32]> },
     TxBundle  { from: NodeId, txs: Vec<Vec<u8>> },
 }
@@ -0,0 +1,101 @@
+//! limiter.rs
+//!
+//! Bounds on the inbound requests a node serves to its peers.
+//!
+//! Serving data to syncing peers must never starve consensus traffic. The
+//! limiter caps how many inbound requests are in flight at once, both in
+//! total and per peer; anything above the cap is refused immediately so the
+//! requester can move on to another source instead of waiting for a timeout.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use atlas_sdk::utils::NodeId;
+
+/// Maximum inbound requests served at the same time.
+pub const MAX_INFLIGHT_REQUESTS: usize = 16;
+
+/// Maximum inbound requests served at the same time to a single peer.
+pub const MAX_INFLIGHT_PER_PEER: usize = 2;
+
+/// Tracks in-flight inbound requests, keyed by request ID.
+#[derive(Debug)]
+pub struct ServeLimiter<R> {
+    max_total: usize,
+    max_per_peer: usize,
+    inflight: HashMap<R, NodeId>,
+    per_peer: HashMap<NodeId, usize>,
+}
+
+impl<R: Hash + Eq> ServeLimiter<R> {
+    pub fn new(max_total: usize, max_per_peer: usize) -> Self {
+        Self {
+            max_total,
+            max_per_peer,
+            inflight: HashMap::new(),
+            per_peer: HashMap::new(),
+        }
+    }
+
+    /// Reserves a slot for `request` from `peer`.
+    ///
+    /// Returns `false` when either limit is reached; the caller should
+    /// refuse the request.
+    pub fn try_acquire(&mut self, peer: &NodeId, request: R) -> bool {
+        let peer_count = self.per_peer.get(peer).copied().unwrap_or(0);
+        if self.inflight.len() >= self.max_total || peer_count >= self.max_per_peer {
+            return false;
+        }
+
+        self.inflight.insert(request, peer.clone());
+        *self.per_peer.entry(peer.clone()).or_default() += 1;
+        true
+    }
+
+    /// Frees the slot held by `request`, once it was answered or failed.
+    pub fn release(&mut self, request: &R) {
+        let Some(peer) = self.inflight.remove(request) else { return };
+
+        if let Some(count) = self.per_peer.get_mut(&peer) {
+            *count -= 1;
+            if *count == 0 {
+                self.per_peer.remove(&peer);
+            }
+        }
+    }
+
+    /// Number of requests currently being served.
+    pub fn inflight(&self) -> usize {
+        self.inflight.len()
+    }
+}
+
+impl<R: Hash + Eq> Default for ServeLimiter<R> {
+    fn default() -> Self {
+        Self::new(MAX_INFLIGHT_REQUESTS, MAX_INFLIGHT_PER_PEER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_per_peer_and_total() {
+        let a = NodeId("a".into());
+        let b = NodeId("b".into());
+        let mut limiter = ServeLimiter::new(3, 2);
+
+        assert!(limiter.try_acquire(&a, 1));
+        assert!(limiter.try_acquire(&a, 2));
+        assert!(!limiter.try_acquire(&a, 3), "per-peer limit");
+
+        assert!(limiter.try_acquire(&b, 4));
+        assert!(!limiter.try_acquire(&b, 5), "total limit");
+
+        limiter.release(&1);
+        limiter.release(&1); // releasing twice is a no-op
+        assert_eq!(limiter.inflight(), 2);
+        assert!(limiter.try_acquire(&a, 6));
+    }
+}
@@ -1,8 +1,10 @@
 pub mod adapter;
 pub mod behaviour;
 pub mod codec;
+pub mod envelope;
 pub mod config;
 pub mod events;
+pub mod limiter;
 pub mod error;
 pub mod protocol;
 pub mod ports;
@@ -3,6 +3,11 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait P2pPublisher: Send + Sync {
     async fn publish(&self, topic: &str, data: Vec<u8>) -> Result<(), String>;
+
+    /// Substitui o payload enviado nos heartbeats periódicos.
+    async fn set_heartbeat(&self, _data: Vec<u8>) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 use tokio::sync::mpsc;
@@ -21,4 +26,11 @@ impl P2pPublisher for AdapterHandle {
             .await
             .map_err(|e| e.to_string())
     }
+
+    async fn set_heartbeat(&self, data: Vec<u8>) -> Result<(), String> {
+        self.cmd_tx
+            .send(AdapterCmd::SetHeartbeat(data))
+            .await
+            .map_err(|e| e.to_string())
+    }
 }
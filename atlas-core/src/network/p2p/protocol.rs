@@ -8,4 +8,10 @@ pub struct TxRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TxBundle {
     pub txs: Vec<Vec<u8>>,
-}
\ No newline at end of file
+}
+
+/// Payload publicado periodicamente em `atlas/heartbeat/v1`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Heartbeat {
+    pub state: crate::env::storage::StateRoot,
+}
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use atlas_sdk::utils::NodeId;
 
 use crate::cluster::node::Node;
+use crate::network::capabilities::{Capabilities, Capability};
 
 
 
@@ -13,6 +14,11 @@ pub enum PeerCommand {
     Disconnected(NodeId),
     Rotate,
     UpdateStats(NodeId, Node),
+    Capabilities(NodeId, Capabilities),
+    /// Prova verificada de mau comportamento do peer.
+    Misbehaved(NodeId),
+    /// Altura anunciada pelo peer no heartbeat.
+    Height(NodeId, u64),
 }
 
 pub enum PeerEvent {
@@ -32,6 +38,15 @@ pub struct PeerManager {
     pub known_peers: HashMap<NodeId, Node>,
     pub max_active: usize,
     pub max_reserve: usize,
+    /// Serviços anunciados por cada peer (via identify).
+    #[serde(default)]
+    pub capabilities: HashMap<NodeId, Capabilities>,
+    /// Provas de mau comportamento recebidas contra cada peer.
+    #[serde(default)]
+    pub misbehavior: HashMap<NodeId, u32>,
+    /// Última altura anunciada por cada peer (não persiste entre execuções).
+    #[serde(skip)]
+    pub heights: HashMap<NodeId, u64>,
 }
 
 impl PeerManager {
@@ -42,6 +57,9 @@ impl PeerManager {
             known_peers: HashMap::new(),
             max_active,
             max_reserve,
+            capabilities: HashMap::new(),
+            misbehavior: HashMap::new(),
+            heights: HashMap::new(),
         }
     }
 
@@ -60,13 +78,15 @@ impl PeerManager {
         }
     }
 
-    /// Tupla de score: confiabilidade (maior é melhor) e latência (menor é melhor).
+    /// Tupla de score: provas de mau comportamento (menos é melhor),
+    /// confiabilidade (maior é melhor) e latência (menor é melhor).
     /// Usamos Reverse(rel) para ordenar decrescente por confiabilidade.
-    fn score_tuple(&self, id: &NodeId) -> (std::cmp::Reverse<i64>, u64) {
+    fn score_tuple(&self, id: &NodeId) -> (u32, std::cmp::Reverse<i64>, u64) {
         let s = self.known_peers.get(id);
         let rel = s.map(|n| (n.reliability_score * 1_000_000.0) as i64).unwrap_or(0);
         let lat = s.and_then(|n| n.latency).unwrap_or(u64::MAX);
-        (std::cmp::Reverse(rel), lat)
+        let reports = self.misbehavior.get(id).copied().unwrap_or(0);
+        (reports, std::cmp::Reverse(rel), lat)
     }
 
     /// true se `a` é melhor que `b`
@@ -128,6 +148,8 @@ impl PeerManager {
         self.active_peers.remove(node_id);
         self.reserve_peers.remove(node_id);
         self.known_peers.remove(node_id);
+        self.capabilities.remove(node_id);
+        self.heights.remove(node_id);
     }
 
     /// Rotação: promove o melhor da reserva se ele for melhor que o pior ativo (máx 1 troca)
@@ -136,7 +158,7 @@ impl PeerManager {
             return (None, None);
         }
         let worst_active = self.active_peers.iter().min_by_key(|id| self.score_tuple(id)).cloned();
-        let best_reserve = self.reserve_peers.iter().max_by(|a, b| self.score_tuple(*b).cmp(&self.score_tuple(*a))).cloned();
+        let best_reserve = self.reserve_peers.iter().max_by(|a, b| self.score_tuple(b).cmp(&self.score_tuple(a))).cloned();
         match (best_reserve, worst_active) {
             (Some(best_r), Some(worst_a)) if self.better(&best_r, &worst_a) => {
                 self.reserve_peers.remove(&best_r);
@@ -149,6 +171,7 @@ impl PeerManager {
         }
     }
 
+    #[allow(dead_code)]
     fn find_worst_active_peer(&self) -> Option<NodeId> {
         self.active_peers.iter().min_by_key(|id| {
             let stats = self.known_peers.get(*id);
@@ -175,6 +198,33 @@ impl PeerManager {
         self.known_peers.keys().cloned().collect()
     }
 
+    /// Mediana das alturas anunciadas pelos peers conhecidos.
+    ///
+    /// A mediana resiste a um peer isolado anunciando uma altura absurda.
+    pub fn network_height(&self) -> Option<u64> {
+        let mut heights: Vec<u64> = self.heights
+            .iter()
+            .filter(|(id, _)| self.known_peers.contains_key(id))
+            .map(|(_, h)| *h)
+            .collect();
+        if heights.is_empty() {
+            return None;
+        }
+        heights.sort_unstable();
+        Some(heights[heights.len() / 2])
+    }
+
+    /// Peers que anunciam `cap` (na versão mínima pedida), do melhor para o pior.
+    pub fn peers_with(&self, cap: Capability, min_version: u32) -> Vec<NodeId> {
+        let mut peers: Vec<NodeId> = self.capabilities
+            .iter()
+            .filter(|(id, caps)| caps.supports(cap, min_version) && self.known_peers.contains_key(id))
+            .map(|(id, _)| id.clone())
+            .collect();
+        peers.sort_by_key(|id| self.score_tuple(id));
+        peers
+    }
+
     pub fn handle_command(&mut self, command: PeerCommand) -> PeerEvent {
         match &command {
             PeerCommand::Register(id, _) => log::debug!("Registering peer: {:?}", id),
@@ -182,6 +232,9 @@ impl PeerManager {
             PeerCommand::Disconnected(id)  => log::debug!("Disconnected {id:?}"),
             PeerCommand::Rotate => log::debug!("Rotating peers"),
             PeerCommand::UpdateStats(id, _) => log::debug!("Updating stats for peer: {:?}", id),
+            PeerCommand::Capabilities(id, caps) => log::debug!("Capabilities for peer {:?}: {}", id, caps),
+            PeerCommand::Misbehaved(id) => log::debug!("Misbehavior proven for peer: {:?}", id),
+            PeerCommand::Height(id, height) => log::debug!("Peer {:?} at height {}", id, height),
         }
     
         match command {
@@ -214,6 +267,73 @@ impl PeerManager {
             PeerCommand::UpdateStats(id, stats) => {
                 self.update_stats(&id, &stats)
             },
+            PeerCommand::Capabilities(id, caps) => {
+                if self.capabilities.get(&id) == Some(&caps) {
+                    PeerEvent::NoChange
+                } else {
+                    self.capabilities.insert(id.clone(), caps);
+                    PeerEvent::Updated(id)
+                }
+            },
+            // só peers conhecidos têm altura registrada, para o mapa não crescer sem limite
+            PeerCommand::Height(id, _) if !self.known_peers.contains_key(&id) => PeerEvent::NoChange,
+            PeerCommand::Height(id, height) => {
+                match self.heights.insert(id.clone(), height) {
+                    Some(previous) if previous == height => PeerEvent::NoChange,
+                    _ => PeerEvent::Updated(id),
+                }
+            },
+            // só peers conhecidos acumulam provas, para o mapa não crescer sem limite
+            PeerCommand::Misbehaved(id) if !self.known_peers.contains_key(&id) => PeerEvent::NoChange,
+            PeerCommand::Misbehaved(id) => {
+                *self.misbehavior.entry(id.clone()).or_default() += 1;
+                // rebaixa o peer para a reserva; a rotação não o promove de volta
+                // enquanto houver peers sem provas contra si
+                if self.active_peers.contains(&id) {
+                    self.demote_or_reserve(&id);
+                    PeerEvent::Demoted(id)
+                } else {
+                    PeerEvent::Updated(id)
+                }
+            },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(heights: &[(&str, u64)]) -> PeerManager {
+        let mut peers = PeerManager::new(8, 8);
+        for (id, height) in heights {
+            let id = NodeId(id.to_string());
+            peers.handle_command(PeerCommand::Register(id.clone(), Node::default()));
+            peers.handle_command(PeerCommand::Height(id, *height));
+        }
+        peers
+    }
+
+    #[test]
+    fn test_network_height_without_peers_is_unknown() {
+        assert_eq!(PeerManager::new(8, 8).network_height(), None);
+    }
+
+    #[test]
+    fn test_network_height_is_the_median_not_the_max() {
+        let peers = manager(&[("a", 10), ("b", 12), ("c", 11), ("d", 1_000_000), ("e", 9)]);
+        assert_eq!(peers.network_height(), Some(11));
+    }
+
+    #[test]
+    fn test_network_height_ignores_dropped_peers() {
+        let mut peers = manager(&[("a", 10), ("b", 50)]);
+        peers.handle_command(PeerCommand::Drop(NodeId("b".into())));
+        assert_eq!(peers.network_height(), Some(10));
+
+        // Altura anunciada por um peer que nunca se registrou também não conta.
+        peers.handle_command(PeerCommand::Height(NodeId("x".into()), 99));
+        assert_eq!(peers.network_height(), Some(10));
+        assert!(!peers.heights.contains_key(&NodeId("x".into())));
+    }
+}
@@ -0,0 +1,204 @@
+//! admin.rs
+//!
+//! Authorization of admin RPCs.
+//!
+//! mTLS only proves that a client holds a certificate from the cluster CA.
+//! Admin RPCs additionally require a signature from one of the ed25519 keys
+//! listed in `admin_keys`, so access does not depend on who can reach the
+//! port or obtain a client certificate. Signatures cover the method, a
+//! timestamp, a nonce and the digest of the request body, so captured
+//! headers cannot be reused with another body. Requests signed more than
+//! [`MAX_ADMIN_CLOCK_SKEW`] away from the node's clock are rejected, and
+//! within that window each nonce is accepted only once.
+
+// Errors are returned straight to tonic handlers as `Status`.
+#![allow(clippy::result_large_err)]
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use atlas_sdk::auth::{
+    admin::{admin_signing_bytes, ADMIN_KEY_HEADER, ADMIN_NONCE_HEADER, ADMIN_SIGNATURE_HEADER, ADMIN_TIMESTAMP_HEADER},
+    Authenticator,
+};
+use prost::Message;
+use sha2::{Digest, Sha256};
+use tonic::{Request, Status};
+
+/// Largest accepted distance between the request timestamp and the node's clock.
+pub const MAX_ADMIN_CLOCK_SKEW: Duration = Duration::from_secs(30);
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// SHA-256 of the protobuf encoding of an admin request body.
+pub fn body_digest<T: Message>(body: &T) -> [u8; 32] {
+    Sha256::digest(body.encode_to_vec()).into()
+}
+
+/// Nonces of recently accepted admin requests, to refuse replays.
+///
+/// A nonce only has to be remembered while its timestamp is within the
+/// allowed skew; older requests already fail the timestamp check.
+#[derive(Debug, Default)]
+pub struct NonceCache {
+    seen: Mutex<HashMap<(String, u64), u64>>,
+}
+
+impl NonceCache {
+    /// Records `nonce` for `key`. Returns `false` if it was already used.
+    fn insert(&self, key: &str, nonce: u64, timestamp_ms: u64) -> bool {
+        let now = now_ms();
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, ts| now.abs_diff(*ts) <= MAX_ADMIN_CLOCK_SKEW.as_millis() as u64);
+        seen.insert((key.to_ascii_lowercase(), nonce), timestamp_ms).is_none()
+    }
+}
+
+fn header<'a, T>(request: &'a Request<T>, key: &str) -> Result<&'a str, Status> {
+    request
+        .metadata()
+        .get(key)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| Status::unauthenticated(format!("missing {} header", key)))
+}
+
+/// Signs `request` as an admin call to `method` with `auth`'s key.
+pub fn sign_request<T: Message>(request: &mut Request<T>, method: &str, auth: &dyn Authenticator) -> Result<(), String> {
+    let timestamp_ms = now_ms();
+    let nonce: u64 = rand::random();
+    let signature = auth.sign(admin_signing_bytes(method, timestamp_ms, nonce, &body_digest(request.get_ref())))?;
+
+    let metadata = request.metadata_mut();
+    for (key, value) in [
+        (ADMIN_KEY_HEADER, hex::encode(auth.public_key())),
+        (ADMIN_TIMESTAMP_HEADER, timestamp_ms.to_string()),
+        (ADMIN_NONCE_HEADER, nonce.to_string()),
+        (ADMIN_SIGNATURE_HEADER, hex::encode(signature)),
+    ] {
+        metadata.insert(key, value.parse().map_err(|e| format!("{}: {}", key, e))?);
+    }
+    Ok(())
+}
+
+/// Checks that `request` carries a fresh signature for `method` and its body
+/// from one of `admin_keys`, with a nonce not seen in `nonces`.
+pub fn authorize<T: Message>(
+    request: &Request<T>,
+    method: &str,
+    admin_keys: &[String],
+    auth: &dyn Authenticator,
+    nonces: &NonceCache,
+) -> Result<(), Status> {
+    if admin_keys.is_empty() {
+        return Err(Status::permission_denied("admin RPCs are disabled: no admin_keys configured"));
+    }
+
+    let key = header(request, ADMIN_KEY_HEADER)?;
+    if !admin_keys.iter().any(|k| k.eq_ignore_ascii_case(key)) {
+        return Err(Status::permission_denied("admin key is not authorized"));
+    }
+
+    let timestamp_ms: u64 = header(request, ADMIN_TIMESTAMP_HEADER)?
+        .parse()
+        .map_err(|_| Status::unauthenticated("invalid admin timestamp"))?;
+    if now_ms().abs_diff(timestamp_ms) > MAX_ADMIN_CLOCK_SKEW.as_millis() as u64 {
+        return Err(Status::unauthenticated("admin request timestamp outside the allowed skew"));
+    }
+    let nonce: u64 = header(request, ADMIN_NONCE_HEADER)?
+        .parse()
+        .map_err(|_| Status::unauthenticated("invalid admin nonce"))?;
+
+    let public_key = hex::decode(key).map_err(|_| Status::unauthenticated("invalid admin key"))?;
+    let signature: [u8; 64] = hex::decode(header(request, ADMIN_SIGNATURE_HEADER)?)
+        .ok()
+        .and_then(|s| s.try_into().ok())
+        .ok_or_else(|| Status::unauthenticated("invalid admin signature"))?;
+
+    let signed = admin_signing_bytes(method, timestamp_ms, nonce, &body_digest(request.get_ref()));
+    if !matches!(auth.verify_with_key(&signed, &signature, &public_key), Ok(true)) {
+        return Err(Status::unauthenticated("admin signature does not verify"));
+    }
+    // Only after the signature, so callers without a key cannot fill the cache.
+    if !nonces.insert(key, nonce, timestamp_ms) {
+        return Err(Status::unauthenticated("admin nonce already used"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::atlas::FaultSettings;
+    use atlas_sdk::auth::ed25519::Ed25519Authenticator;
+
+    const METHOD: &str = "/atlas.ProposalService/SetFaults";
+
+    fn admin() -> (Ed25519Authenticator, Vec<String>) {
+        let admin = Ed25519Authenticator::from_bytes(&[1u8; 32]).unwrap();
+        let keys = vec![hex::encode(admin.public_key())];
+        (admin, keys)
+    }
+
+    fn signed(settings: FaultSettings, auth: &Ed25519Authenticator) -> Request<FaultSettings> {
+        let mut request = Request::new(settings);
+        sign_request(&mut request, METHOD, auth).unwrap();
+        request
+    }
+
+    #[test]
+    fn test_only_listed_keys_with_fresh_signatures_pass() {
+        let (admin, keys) = admin();
+        let other = Ed25519Authenticator::from_bytes(&[2u8; 32]).unwrap();
+
+        let mut request = signed(FaultSettings::default(), &admin);
+        assert!(authorize(&request, "/atlas.ProposalService/Other", &keys, &admin, &NonceCache::default()).is_err());
+        assert!(authorize(&request, METHOD, &[], &admin, &NonceCache::default()).is_err());
+        assert!(authorize(&request, METHOD, &keys, &admin, &NonceCache::default()).is_ok());
+
+        let stranger = signed(FaultSettings::default(), &other);
+        assert_eq!(
+            authorize(&stranger, METHOD, &keys, &admin, &NonceCache::default()).unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+
+        let stale = (now_ms() - 2 * MAX_ADMIN_CLOCK_SKEW.as_millis() as u64).to_string();
+        request.metadata_mut().insert(ADMIN_TIMESTAMP_HEADER, stale.parse().unwrap());
+        assert!(authorize(&request, METHOD, &keys, &admin, &NonceCache::default()).is_err());
+    }
+
+    #[test]
+    fn test_signature_binds_the_request_body() {
+        let (admin, keys) = admin();
+        let request = signed(FaultSettings { proposal_delay_ms: 10, ..Default::default() }, &admin);
+
+        let (metadata, _, _) = request.into_parts();
+        let swapped = Request::from_parts(
+            metadata,
+            Default::default(),
+            FaultSettings { crash_after_commits: 1, ..Default::default() },
+        );
+        assert!(authorize(&swapped, METHOD, &keys, &admin, &NonceCache::default()).is_err());
+    }
+
+    #[test]
+    fn test_replayed_or_missing_nonce_is_rejected() {
+        let (admin, keys) = admin();
+        let nonces = NonceCache::default();
+
+        let mut request = signed(FaultSettings::default(), &admin);
+        assert!(authorize(&request, METHOD, &keys, &admin, &nonces).is_ok());
+        assert!(authorize(&request, METHOD, &keys, &admin, &nonces).is_err());
+
+        let fresh = signed(FaultSettings::default(), &admin);
+        assert!(authorize(&fresh, METHOD, &keys, &admin, &nonces).is_ok());
+
+        request.metadata_mut().remove(ADMIN_NONCE_HEADER);
+        assert!(authorize(&request, METHOD, &keys, &admin, &NonceCache::default()).is_err());
+    }
+}
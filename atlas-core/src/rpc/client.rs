@@ -42,6 +42,7 @@ pub async fn submit_proposal(
 
         let request = tonic::Request::new(ProposalRequest {
             content: content.clone(),
+            ..Default::default()
         });
 
         match client.submit_proposal(request).await {
@@ -0,0 +1,409 @@
+//! jsonrpc.rs
+//!
+//! JSON-RPC 2.0 endpoint.
+//!
+//! Serves the client API as JSON-RPC over plain HTTP POST, for tooling that
+//! speaks JSON-RPC rather than gRPC (client libraries, load generators).
+//! Batches and notifications follow the 2.0 spec. Like the gRPC server it
+//! only runs on the leader, and it is only started when `jsonrpc_addr` is
+//! set in the config. It has no TLS and no client authentication, so write
+//! methods are only served when it is bound to a loopback address; on any
+//! other interface they fail with [`WRITES_DISABLED`] and only reads work.
+//!
+//! Methods:
+//! - `atlas_sendTransaction(content)` submits a proposal and returns its ID.
+//! - `atlas_blockNumber(tag)` returns the committed height.
+//! - `atlas_getBlockByHeight(height, tag)` returns an approved proposal, or `null`.
+//! - `atlas_getProposal(id, tag)` returns a pending or committed proposal, or `null`.
+//!
+//! `tag` is optional: `"latest"` (default) or `"finalized"`, which only
+//! counts commits at least `finality_depth` heights below the head and
+//! refuses with [`NOT_FINALIZED`] newer blocks and proposals not yet
+//! finalized (pending ones included).
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use hyper::body::HttpBody;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde_json::{json, Value};
+
+use crate::network::p2p::ports::P2pPublisher;
+use crate::runtime::maestro::Maestro;
+
+/// Largest request body accepted, in bytes.
+pub const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Largest number of calls accepted in one batch.
+pub const MAX_BATCH_LEN: usize = 100;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// Server-defined: the requested data exists but is not finalized yet.
+pub const NOT_FINALIZED: i64 = -32001;
+/// Server-defined: write methods are refused on a non-loopback bind.
+pub const WRITES_DISABLED: i64 = -32002;
+
+/// Methods that change state, served only on loopback binds.
+const WRITE_METHODS: &[&str] = &["atlas_sendTransaction"];
+
+/// Error member of a JSON-RPC response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+fn response(id: Value, outcome: Result<Value, RpcError>) -> Value {
+    match outcome {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err(e) => json!({ "jsonrpc": "2.0", "error": { "code": e.code, "message": e.message }, "id": id }),
+    }
+}
+
+/// Handles a request body, single call or batch, dispatching each call to
+/// `call(method, params)`.
+///
+/// Returns `None` when nothing should be sent back (only notifications).
+pub async fn handle_body<F, Fut>(body: &[u8], call: F) -> Option<Value>
+where
+    F: Fn(String, Value) -> Fut,
+    Fut: Future<Output = Result<Value, RpcError>>,
+{
+    let parsed: Value = match serde_json::from_slice(body) {
+        Ok(v) => v,
+        Err(e) => return Some(response(Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string())))),
+    };
+
+    match parsed {
+        Value::Array(calls) if calls.is_empty() => {
+            Some(response(Value::Null, Err(RpcError::new(INVALID_REQUEST, "empty batch"))))
+        }
+        Value::Array(calls) if calls.len() > MAX_BATCH_LEN => Some(response(
+            Value::Null,
+            Err(RpcError::new(INVALID_REQUEST, format!("batch larger than {}", MAX_BATCH_LEN))),
+        )),
+        Value::Array(calls) => {
+            let mut replies = Vec::new();
+            for c in calls {
+                replies.extend(handle_call(c, &call).await);
+            }
+            (!replies.is_empty()).then_some(Value::Array(replies))
+        }
+        single => handle_call(single, &call).await,
+    }
+}
+
+async fn handle_call<F, Fut>(request: Value, call: &F) -> Option<Value>
+where
+    F: Fn(String, Value) -> Fut,
+    Fut: Future<Output = Result<Value, RpcError>>,
+{
+    let Value::Object(mut fields) = request else {
+        return Some(response(Value::Null, Err(RpcError::new(INVALID_REQUEST, "request must be an object"))));
+    };
+    // without an `id` it is a notification: run it, but send nothing back
+    let id = fields.remove("id");
+    let reply_id = id.clone().unwrap_or(Value::Null);
+
+    if fields.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Some(response(reply_id, Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""))));
+    }
+    let Some(Value::String(method)) = fields.remove("method") else {
+        return Some(response(reply_id, Err(RpcError::new(INVALID_REQUEST, "missing method"))));
+    };
+    let params = fields.remove("params").unwrap_or(Value::Array(Vec::new()));
+    if !params.is_array() && !params.is_object() {
+        return Some(response(reply_id, Err(RpcError::invalid_params("params must be an array or object"))));
+    }
+
+    let outcome = call(method, params).await;
+    id.map(|id| response(id, outcome))
+}
+
+/// Positional (`[value]`) or named (`{"name": value}`) parameter `index`.
+fn param<'a>(params: &'a Value, index: usize, name: &str) -> Option<&'a Value> {
+    match params {
+        Value::Array(values) => values.get(index),
+        Value::Object(fields) => fields.get(name),
+        _ => None,
+    }
+}
+
+fn string_param(params: &Value, index: usize, name: &str) -> Result<String, RpcError> {
+    param(params, index, name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| RpcError::invalid_params(format!("expected string parameter `{}`", name)))
+}
+
+fn u64_param(params: &Value, index: usize, name: &str) -> Result<u64, RpcError> {
+    param(params, index, name)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| RpcError::invalid_params(format!("expected unsigned integer parameter `{}`", name)))
+}
+
+/// Optional `tag` parameter; `true` for `"finalized"`.
+fn finalized_param(params: &Value, index: usize) -> Result<bool, RpcError> {
+    match param(params, index, "tag").map(|v| v.as_str()) {
+        None | Some(Some("latest")) => Ok(false),
+        Some(Some("finalized")) => Ok(true),
+        _ => Err(RpcError::invalid_params("tag must be \"latest\" or \"finalized\"")),
+    }
+}
+
+/// Refuses write methods unless the server is bound to loopback: unlike the
+/// gRPC server, this endpoint does not check client certificates.
+fn check_write(method: &str, addr: SocketAddr) -> Result<(), RpcError> {
+    if WRITE_METHODS.contains(&method) && !addr.ip().is_loopback() {
+        return Err(RpcError::new(
+            WRITES_DISABLED,
+            format!("{} is only served when JSON-RPC is bound to loopback", method),
+        ));
+    }
+    Ok(())
+}
+
+async fn dispatch<P: P2pPublisher + 'static>(
+    maestro: &Maestro<P>,
+    addr: SocketAddr,
+    method: &str,
+    params: &Value,
+) -> Result<Value, RpcError> {
+    check_write(method, addr)?;
+    let cluster = &maestro.cluster;
+
+    match method {
+        "atlas_sendTransaction" => {
+            let content = string_param(params, 0, "content")?;
+            let request_id = uuid::Uuid::new_v4().to_string();
+            let id = maestro
+                .submit_external_proposal(content, request_id.clone())
+                .await
+                .map_err(|e| RpcError::new(INTERNAL_ERROR, e))?;
+            Ok(json!({ "proposal_id": id, "request_id": request_id }))
+        }
+        "atlas_blockNumber" => Ok(json!(if finalized_param(params, 0)? {
+            cluster.finalized_height().await
+        } else {
+            cluster.local_env.storage.read().await.state_root().height
+        })),
+        "atlas_getBlockByHeight" => {
+            let height = u64_param(params, 0, "height")?;
+            if finalized_param(params, 1)? && height > cluster.finalized_height().await {
+                return Err(RpcError::new(NOT_FINALIZED, format!("height {} is not finalized yet", height)));
+            }
+            Ok(cluster.block_at(height).await.map_or(Value::Null, |(p, qc)| json!({
+                "height": height,
+                "id": p.id,
+                "proposer": p.proposer.0,
+                "content": p.content,
+                "parent": p.parent,
+                "request_id": p.request_id,
+                "signers": qc.map(|qc| qc.votes.into_iter().map(|v| v.voter.0).collect::<Vec<_>>()).unwrap_or_default(),
+            })))
+        }
+        "atlas_getProposal" => {
+            let id = string_param(params, 0, "id")?;
+            let finalized_only = finalized_param(params, 1)?;
+            let found = cluster.find_proposal(&id).await;
+            if found.is_some() && finalized_only {
+                let finalized = match cluster.height_of(&id).await {
+                    Some(height) => height <= cluster.finalized_height().await,
+                    None => false,
+                };
+                if !finalized {
+                    return Err(RpcError::new(NOT_FINALIZED, format!("proposal {} is not finalized yet", id)));
+                }
+            }
+            Ok(found.map_or(Value::Null, |(p, committed)| json!({
+                "id": p.id,
+                "proposer": p.proposer.0,
+                "content": p.content,
+                "parent": p.parent,
+                "request_id": p.request_id,
+                "committed": committed,
+            })))
+        }
+        other => Err(RpcError::new(METHOD_NOT_FOUND, format!("method not found: {}", other))),
+    }
+}
+
+async fn read_body(mut body: Body) -> Result<Vec<u8>, StatusCode> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| StatusCode::BAD_REQUEST)?;
+        if bytes.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+async fn serve_request<P: P2pPublisher + 'static>(
+    maestro: Arc<Maestro<P>>,
+    addr: SocketAddr,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let status = |code: StatusCode| Response::builder().status(code).body(Body::empty()).unwrap();
+
+    if request.method() != Method::POST {
+        return Ok(status(StatusCode::METHOD_NOT_ALLOWED));
+    }
+    let body = match read_body(request.into_body()).await {
+        Ok(body) => body,
+        Err(code) => return Ok(status(code)),
+    };
+
+    let reply = handle_body(&body, |method, params| {
+        let maestro = Arc::clone(&maestro);
+        async move { dispatch(&maestro, addr, &method, &params).await }
+    })
+    .await;
+
+    Ok(match reply {
+        Some(reply) => Response::builder()
+            .header("content-type", "application/json")
+            .body(Body::from(reply.to_string()))
+            .unwrap(),
+        None => status(StatusCode::NO_CONTENT),
+    })
+}
+
+/// Serves JSON-RPC on `addr` until the task is aborted.
+pub async fn run_server<P: P2pPublisher + 'static>(
+    maestro: Arc<Maestro<P>>,
+    addr: SocketAddr,
+) -> Result<(), hyper::Error> {
+    println!("[JSON-RPC] Servidor escutando em {}", addr);
+    if !addr.ip().is_loopback() {
+        println!("[JSON-RPC] Endereço fora do loopback: {} desabilitado(s)", WRITE_METHODS.join(", "));
+    }
+
+    let make_service = make_service_fn(move |_| {
+        let maestro = Arc::clone(&maestro);
+        async move { Ok::<_, Infallible>(service_fn(move |req| serve_request(Arc::clone(&maestro), addr, req))) }
+    });
+
+    Server::try_bind(&addr)?.serve(make_service).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn echo(method: String, params: Value) -> Result<Value, RpcError> {
+        match method.as_str() {
+            "echo" => Ok(params),
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, "no")),
+        }
+    }
+
+    fn run(body: &str) -> Option<Value> {
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        rt.block_on(handle_body(body.as_bytes(), echo))
+    }
+
+    #[test]
+    fn test_single_call_and_errors() {
+        let ok = run(r#"{"jsonrpc":"2.0","method":"echo","params":[1],"id":7}"#).unwrap();
+        assert_eq!(ok, json!({ "jsonrpc": "2.0", "result": [1], "id": 7 }));
+
+        let missing = run(r#"{"jsonrpc":"2.0","method":"nope","id":"a"}"#).unwrap();
+        assert_eq!(missing["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(missing["id"], "a");
+
+        assert_eq!(run("{not json").unwrap()["error"]["code"], PARSE_ERROR);
+        assert_eq!(run(r#"{"method":"echo","id":1}"#).unwrap()["error"]["code"], INVALID_REQUEST);
+        assert_eq!(run(r#"{"jsonrpc":"2.0","method":"echo","params":3,"id":1}"#).unwrap()["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_finalized_tag() {
+        assert_eq!(finalized_param(&json!([]), 0), Ok(false));
+        assert_eq!(finalized_param(&json!([5, "finalized"]), 1), Ok(true));
+        assert_eq!(finalized_param(&json!({ "tag": "latest" }), 0), Ok(false));
+        assert!(finalized_param(&json!(["pending"]), 0).is_err());
+    }
+
+    #[test]
+    fn test_writes_only_on_loopback() {
+        let loopback: SocketAddr = "127.0.0.1:8545".parse().unwrap();
+        let public: SocketAddr = "0.0.0.0:8545".parse().unwrap();
+
+        assert!(check_write("atlas_sendTransaction", loopback).is_ok());
+        assert!(check_write("atlas_sendTransaction", "[::1]:8545".parse().unwrap()).is_ok());
+        assert_eq!(check_write("atlas_sendTransaction", public).unwrap_err().code, WRITES_DISABLED);
+        assert!(check_write("atlas_blockNumber", public).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reads_honor_the_finalized_tag() {
+        use crate::rpc::server::tests::finality_node;
+        use crate::runtime::maestro::tests::maestro_with;
+
+        let node = finality_node().await;
+        node.local_env.engine.lock().await.add_proposal(crate::env::proposal::Proposal {
+            id: "pending".to_string(),
+            proposer: atlas_sdk::utils::NodeId("n1".to_string()),
+            content: "{}".to_string(),
+            parent: None,
+            signature: [0u8; 64],
+            public_key: vec![],
+            request_id: None,
+        });
+        let maestro = maestro_with(node);
+        let addr: SocketAddr = "127.0.0.1:8545".parse().unwrap();
+        let call = |method: &'static str, params: Value| {
+            let maestro = &maestro;
+            async move { dispatch(maestro, addr, method, &params).await }
+        };
+
+        assert_eq!(call("atlas_blockNumber", json!([])).await, Ok(json!(3)));
+        assert_eq!(call("atlas_blockNumber", json!(["finalized"])).await, Ok(json!(1)));
+
+        assert_eq!(call("atlas_getBlockByHeight", json!([2])).await.unwrap()["id"], "p2");
+        assert_eq!(call("atlas_getBlockByHeight", json!([2, "finalized"])).await.unwrap_err().code, NOT_FINALIZED);
+        assert_eq!(call("atlas_getBlockByHeight", json!([1, "finalized"])).await.unwrap()["id"], "p1");
+
+        for id in ["p2", "pending"] {
+            assert_eq!(call("atlas_getProposal", json!([id])).await.unwrap()["id"], id);
+            assert_eq!(call("atlas_getProposal", json!([id, "finalized"])).await.unwrap_err().code, NOT_FINALIZED);
+        }
+        assert_eq!(call("atlas_getProposal", json!(["p1", "finalized"])).await.unwrap()["committed"], true);
+        assert_eq!(call("atlas_getProposal", json!(["nope", "finalized"])).await, Ok(Value::Null));
+    }
+
+    #[test]
+    fn test_batch_skips_notifications() {
+        let reply = run(r#"[
+            {"jsonrpc":"2.0","method":"echo","params":{"a":1},"id":1},
+            {"jsonrpc":"2.0","method":"echo","params":[2]},
+            5
+        ]"#).unwrap();
+        let replies = reply.as_array().unwrap();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[0]["result"], json!({ "a": 1 }));
+        assert_eq!(replies[1]["error"]["code"], INVALID_REQUEST);
+
+        assert!(run(r#"[{"jsonrpc":"2.0","method":"echo"}]"#).is_none());
+        assert_eq!(run("[]").unwrap()["error"]["code"], INVALID_REQUEST);
+    }
+}
@@ -1,7 +1,10 @@
 // Este arquivo define o módulo RPC e importa o código gerado pelo Prost/Tonic.
 
+pub mod admin;
 pub mod server;
 pub mod client;
+pub mod jsonrpc;
+pub mod pagination;
 
 pub mod atlas {
     tonic::include_proto!("atlas");
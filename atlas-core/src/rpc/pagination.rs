@@ -0,0 +1,211 @@
+//! pagination.rs
+//!
+//! Shared pagination conventions for list RPCs.
+//!
+//! Every list endpoint sorts its items by a stable string key, returns at
+//! most `MAX_PAGE_LIMIT` items per page, and hands back an opaque cursor
+//! pointing after the last item returned. Clients may also pass a field
+//! mask to trim the records they receive, and a `ListFilter` to narrow the
+//! listing by height range or node.
+
+// Errors are returned straight to tonic handlers as `Status`.
+#![allow(clippy::result_large_err)]
+
+use std::collections::HashSet;
+
+use tonic::Status;
+
+use crate::rpc::atlas::{ListFilter, PageRequest};
+
+/// Page size used when the client does not set a limit.
+pub const DEFAULT_PAGE_LIMIT: usize = 50;
+
+/// Hard upper bound on the number of items returned in one page.
+pub const MAX_PAGE_LIMIT: usize = 500;
+
+/// A single page of results.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+
+    /// Cursor for the next page, or empty when this is the last one.
+    pub next_cursor: String,
+}
+
+/// Encodes a sort key as an opaque cursor.
+pub fn encode_cursor(key: &str) -> String {
+    hex::encode(key)
+}
+
+/// Decodes a cursor produced by [`encode_cursor`].
+pub fn decode_cursor(cursor: &str) -> Result<String, Status> {
+    hex::decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| Status::invalid_argument("invalid page cursor"))
+}
+
+/// Clamps the requested limit into `1..=MAX_PAGE_LIMIT`.
+pub fn effective_limit(requested: u32) -> usize {
+    match requested as usize {
+        0 => DEFAULT_PAGE_LIMIT,
+        n => n.min(MAX_PAGE_LIMIT),
+    }
+}
+
+/// Page size and decoded cursor of `req`.
+///
+/// Lets a listing pick its own candidates (at most `limit + 1` items after
+/// the cursor) before handing them to [`paginate`].
+pub fn page_bounds(req: Option<&PageRequest>) -> Result<(usize, Option<String>), Status> {
+    Ok(match req {
+        Some(r) if !r.cursor.is_empty() => (effective_limit(r.limit), Some(decode_cursor(&r.cursor)?)),
+        Some(r) => (effective_limit(r.limit), None),
+        None => (DEFAULT_PAGE_LIMIT, None),
+    })
+}
+
+/// Sorts `items` by `key` and returns the page described by `req`.
+///
+/// Items whose key is less than or equal to the cursor are skipped, so a
+/// page stays stable even if earlier items are added between calls.
+pub fn paginate<T, F>(mut items: Vec<T>, key: F, req: Option<&PageRequest>) -> Result<Page<T>, Status>
+where
+    F: Fn(&T) -> &str,
+{
+    let (limit, after) = page_bounds(req)?;
+
+    items.sort_by(|a, b| key(a).cmp(key(b)));
+
+    let mut page: Vec<T> = items
+        .into_iter()
+        .filter(|item| after.as_deref().is_none_or(|a| key(item) > a))
+        .take(limit + 1)
+        .collect();
+
+    let next_cursor = if page.len() > limit {
+        page.truncate(limit);
+        page.last().map(|item| encode_cursor(key(item))).unwrap_or_default()
+    } else {
+        String::new()
+    };
+
+    Ok(Page { items: page, next_cursor })
+}
+
+/// Sort key for height-ordered listings, zero-padded so text order follows height.
+pub fn height_key(height: u64) -> String {
+    format!("{:020}", height)
+}
+
+/// Returns `true` if `height` is inside the filter's range.
+///
+/// A `0` bound leaves that side open; items without a height only match
+/// when no range is set.
+pub fn in_height_range(filter: &ListFilter, height: Option<u64>) -> bool {
+    if filter.from_height == 0 && filter.to_height == 0 {
+        return true;
+    }
+    height.is_some_and(|h| h >= filter.from_height && (filter.to_height == 0 || h <= filter.to_height))
+}
+
+/// Set of fields requested by the client.
+///
+/// An empty mask selects every field.
+#[derive(Debug, Clone, Default)]
+pub struct FieldMask {
+    fields: HashSet<String>,
+}
+
+impl FieldMask {
+    /// Builds a mask from the request, rejecting fields not in `allowed`.
+    pub fn from_request(req: Option<&PageRequest>, allowed: &[&str]) -> Result<Self, Status> {
+        let mut fields = HashSet::new();
+        for field in req.map(|r| r.fields.as_slice()).unwrap_or_default() {
+            if !allowed.contains(&field.as_str()) {
+                return Err(Status::invalid_argument(format!("unknown field in mask: {}", field)));
+            }
+            fields.insert(field.clone());
+        }
+        Ok(Self { fields })
+    }
+
+    /// Returns `true` if `field` should be included in the response.
+    pub fn includes(&self, field: &str) -> bool {
+        self.fields.is_empty() || self.fields.contains(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(limit: u32, cursor: &str) -> PageRequest {
+        PageRequest { limit, cursor: cursor.to_string(), fields: vec![] }
+    }
+
+    #[test]
+    fn test_height_range_bounds() {
+        let range = |from_height, to_height| ListFilter { from_height, to_height, ..Default::default() };
+
+        assert!(in_height_range(&range(0, 0), None));
+        assert!(!in_height_range(&range(2, 0), None));
+        assert!(in_height_range(&range(2, 0), Some(9)));
+        assert!(!in_height_range(&range(2, 4), Some(5)));
+        assert!(in_height_range(&range(0, 4), Some(4)));
+        assert!(height_key(9) < height_key(10));
+    }
+
+    fn ids(n: usize) -> Vec<String> {
+        (0..n).rev().map(|i| format!("id-{:03}", i)).collect()
+    }
+
+    #[test]
+    fn test_paginate_walks_all_pages_in_order() {
+        let mut cursor = String::new();
+        let mut seen = Vec::new();
+
+        loop {
+            let page = paginate(ids(7), |s| s.as_str(), Some(&req(3, &cursor))).unwrap();
+            seen.extend(page.items);
+            if page.next_cursor.is_empty() {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+
+        let mut expected = ids(7);
+        expected.sort();
+        assert_eq!(seen, expected);
+    }
+
+    #[test]
+    fn test_paginate_caps_limit() {
+        let page = paginate(ids(MAX_PAGE_LIMIT + 10), |s| s.as_str(), Some(&req(u32::MAX, ""))).unwrap();
+        assert_eq!(page.items.len(), MAX_PAGE_LIMIT);
+        assert!(!page.next_cursor.is_empty());
+
+        let page = paginate(ids(DEFAULT_PAGE_LIMIT + 1), |s| s.as_str(), None).unwrap();
+        assert_eq!(page.items.len(), DEFAULT_PAGE_LIMIT);
+    }
+
+    #[test]
+    fn test_paginate_rejects_bad_cursor() {
+        let err = paginate(ids(3), |s| s.as_str(), Some(&req(1, "zz"))).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_field_mask() {
+        let mut r = req(0, "");
+        assert!(FieldMask::from_request(Some(&r), &["id"]).unwrap().includes("content"));
+
+        r.fields = vec!["id".into()];
+        let mask = FieldMask::from_request(Some(&r), &["id", "content"]).unwrap();
+        assert!(mask.includes("id"));
+        assert!(!mask.includes("content"));
+
+        r.fields = vec!["nope".into()];
+        assert!(FieldMask::from_request(Some(&r), &["id"]).is_err());
+    }
+}
@@ -1,18 +1,78 @@
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
 use std::sync::Arc;
+use futures::{stream, Stream};
+use tokio::sync::broadcast::error::RecvError;
 use tonic::{Request, Response, Status};
 use tonic::transport::{Server, ServerTlsConfig, Identity, Certificate};
 
-use crate::runtime::maestro::Maestro;
+use crate::cluster::core::Cluster;
+use crate::runtime::maestro::{Maestro, WriteConcern, DEFAULT_WRITE_TIMEOUT, MAX_WRITE_TIMEOUT};
 use crate::network::p2p::ports::P2pPublisher;
 use crate::rpc::atlas::{
+    self as atlas,
     proposal_service_server::{ProposalService, ProposalServiceServer},
-    ProposalRequest, ProposalReply,
+    Attestation, FaultSettings, ListAttestationsReply, ListFilter, ListPeersReply, ListProposalsReply, ListRequest, NodeInfo, NodeInfoRequest, PeerRecord, ScrubSummary,
+    Block, Commitment, ProposalQuery, ProposalRecord, ProposalStatus, ProposalRequest, ProposalReply, ProposalWithQc, QcVote,
+    QuorumCertificate, ResultRecord, ListVotesReply, VoteRecord, ValidatorPerformance, ValidatorQuery,
+    StageEvent, StageHistogram, StageLatencies, StageTiming, StatsRequest, SubscribeRequest,
 };
+use crate::rpc::admin;
+use crate::rpc::pagination::{height_key, in_height_range, page_bounds, paginate, FieldMask};
+use crate::env::{proposal::Proposal, storage::Storage, timing::Stage};
+use atlas_sdk::env::consensus::certificate::QuorumCertificate as QcProof;
+use crate::version;
 
 
 // Define a struct para o nosso serviço. Ela precisa de acesso ao Maestro.
 pub struct MyProposalService<P: P2pPublisher> {
     maestro: Arc<Maestro<P>>,
+    admin_nonces: admin::NonceCache,
+}
+
+/// Com `COMMITMENT_FINALIZED`, recusa propostas que ainda não têm `finality_depth` confirmações.
+async fn ensure_finalized(cluster: &Cluster, id: &str, commitment: Commitment) -> Result<(), Status> {
+    if commitment == Commitment::Latest {
+        return Ok(());
+    }
+    let Some(height) = cluster.height_of(id).await else {
+        return Err(Status::failed_precondition(format!("Proposta {} não está commitada", id)));
+    };
+    if height > cluster.finalized_height().await {
+        return Err(Status::failed_precondition(format!(
+            "Proposta {} na altura {} ainda não tem {} confirmações",
+            id, height, cluster.finality_depth
+        )));
+    }
+    Ok(())
+}
+
+/// Com `COMMITMENT_FINALIZED`, limita a faixa de alturas do filtro às
+/// finalizadas (o que também tira as pendentes da listagem).
+///
+/// Devolve `false` quando nada pode entrar: ainda não há altura finalizada.
+fn cap_to_finalized(filter: &mut ListFilter, finalized: u64) -> bool {
+    if filter.commitment() == Commitment::Latest {
+        return true;
+    }
+    if finalized == 0 {
+        return false;
+    }
+    filter.to_height = match filter.to_height {
+        0 => finalized,
+        to => to.min(finalized),
+    };
+    true
+}
+
+/// Recusa `COMMITMENT_FINALIZED` nas assinaturas de propostas ainda não finalizadas.
+// o `Status` vai direto para o tonic
+#[allow(clippy::result_large_err)]
+fn latest_only(commitment: Commitment) -> Result<(), Status> {
+    match commitment {
+        Commitment::Latest => Ok(()),
+        Commitment::Finalized => Err(Status::invalid_argument("esta assinatura não tem dados finalizados")),
+    }
 }
 
 #[tonic::async_trait]
@@ -22,24 +82,561 @@ impl<P: P2pPublisher + 'static> ProposalService for MyProposalService<P> {
         &self,
         request: Request<ProposalRequest>,
     ) -> Result<Response<ProposalReply>, Status> {
-        println!("gRPC: Recebida chamada para SubmitProposal");
+        let request_id = request_id_from(&request);
+        println!("gRPC: Recebida chamada para SubmitProposal (request_id={})", request_id);
 
         let req = request.into_inner();
+        let concern = write_concern_from(req.write_concern());
+        let timeout = match req.timeout_ms {
+            0 => DEFAULT_WRITE_TIMEOUT,
+            ms => std::time::Duration::from_millis(ms).min(MAX_WRITE_TIMEOUT),
+        };
+        let commits = self.maestro.cluster.stage_events.subscribe();
 
         // Aqui, chamamos a lógica de negócio que já existe no Maestro.
-        match self.maestro.submit_external_proposal(req.content).await {
+        match self.maestro.submit_external_proposal(req.content, request_id.clone()).await {
             Ok(proposal_id) => {
+                if !self.maestro.await_write_concern(commits, &proposal_id, concern, timeout).await {
+                    return Err(Status::deadline_exceeded(format!(
+                        "Proposta {} submetida, mas não atingiu '{}' em {:?} (request_id={})",
+                        proposal_id, concern.as_str(), timeout, request_id
+                    )));
+                }
                 let reply = ProposalReply {
                     message: "Proposta submetida com sucesso".into(),
                     proposal_id,
+                    request_id: request_id.clone(),
+                    write_concern: concern.as_str().to_string(),
                 };
-                Ok(Response::new(reply))
+                let mut response = Response::new(reply);
+                if let Ok(value) = request_id.parse() {
+                    response.metadata_mut().insert(REQUEST_ID_HEADER, value);
+                }
+                Ok(response)
             }
             Err(e) => {
-                Err(Status::internal(format!("Falha ao submeter proposta: {}", e)))
+                Err(Status::internal(format!("Falha ao submeter proposta (request_id={}): {}", request_id, e)))
+            }
+        }
+    }
+
+    async fn get_proposal(
+        &self,
+        request: Request<ProposalQuery>,
+    ) -> Result<Response<ProposalRecord>, Status> {
+        let query = request.into_inner();
+        let id = query.proposal_id.clone();
+        ensure_finalized(&self.maestro.cluster, &id, query.commitment()).await?;
+
+        match self.maestro.cluster.find_proposal(&id).await {
+            Some((proposal, committed)) => {
+                let mut record = proposal_record(proposal, committed, &FieldMask::default());
+                record.timings = self.maestro.cluster.timings.lock().await
+                    .record(&id)
+                    .into_iter()
+                    .map(|(stage, elapsed)| StageTiming {
+                        stage: stage.as_str().to_string(),
+                        elapsed_us: elapsed.as_micros() as u64,
+                    })
+                    .collect();
+                Ok(Response::new(record))
+            }
+            None => Err(Status::not_found(format!("Proposta {} não encontrada", id))),
+        }
+    }
+
+    async fn get_result(
+        &self,
+        request: Request<ProposalQuery>,
+    ) -> Result<Response<ResultRecord>, Status> {
+        let query = request.into_inner();
+        let id = query.proposal_id.clone();
+        ensure_finalized(&self.maestro.cluster, &id, query.commitment()).await?;
+
+        match self.maestro.cluster.find_result(&id).await {
+            Some(result) => Ok(Response::new(ResultRecord {
+                proposal_id: result.proposal_id,
+                approved: result.approved,
+                votes_received: result.votes_received as u64,
+            })),
+            None => Err(Status::not_found(format!("Resultado para {} não encontrado", id))),
+        }
+    }
+
+    async fn get_proposal_with_qc(
+        &self,
+        request: Request<ProposalQuery>,
+    ) -> Result<Response<ProposalWithQc>, Status> {
+        let query = request.into_inner();
+        let id = query.proposal_id.clone();
+        ensure_finalized(&self.maestro.cluster, &id, query.commitment()).await?;
+
+        match self.maestro.cluster.get_proposal_with_qc(&id).await {
+            Some((proposal, qc)) => Ok(Response::new(ProposalWithQc {
+                proposal: Some(proposal_record(proposal, true, &FieldMask::default())),
+                qc: Some(qc_message(qc)),
+            })),
+            None => Err(Status::not_found(format!("Proposta {} não está finalizada", id))),
+        }
+    }
+
+    async fn list_attestations(
+        &self,
+        request: Request<ListRequest>,
+    ) -> Result<Response<ListAttestationsReply>, Status> {
+        let req = request.into_inner();
+        let page_req = req.page;
+        let mut filter = req.filter.unwrap_or_default();
+        if !cap_to_finalized(&mut filter, self.maestro.cluster.finalized_height().await) {
+            return Ok(Response::new(ListAttestationsReply::default()));
+        }
+
+        let attestations: Vec<(String, Attestation)> = self.maestro.cluster.attestations().await
+            .into_iter()
+            .filter(|a| in_height_range(&filter, Some(a.height)))
+            .filter(|a| filter.signer.is_empty() || a.signers.iter().any(|s| s.0 == filter.signer))
+            .map(|a| (
+                height_key(a.height),
+                Attestation {
+                    height: a.height,
+                    proposal_id: a.proposal_id,
+                    signers: a.signers.into_iter().map(|s| s.0).collect(),
+                },
+            ))
+            .collect();
+        let page = paginate(attestations, |(key, _)| key.as_str(), page_req.as_ref())?;
+
+        Ok(Response::new(ListAttestationsReply {
+            attestations: page.items.into_iter().map(|(_, a)| a).collect(),
+            next_cursor: page.next_cursor,
+        }))
+    }
+
+    async fn list_votes(
+        &self,
+        request: Request<ProposalQuery>,
+    ) -> Result<Response<ListVotesReply>, Status> {
+        let query = request.into_inner();
+        let id = query.proposal_id.clone();
+        ensure_finalized(&self.maestro.cluster, &id, query.commitment()).await?;
+
+        let votes = self.maestro.cluster.vote_log(&id).await
+            .into_iter()
+            .map(|r| VoteRecord {
+                voter: r.voter.0,
+                vote: format!("{:?}", r.vote),
+                received_at_ms: r.received_at_ms,
+            })
+            .collect();
+
+        Ok(Response::new(ListVotesReply { proposal_id: id, votes }))
+    }
+
+    async fn get_validator_performance(
+        &self,
+        request: Request<ValidatorQuery>,
+    ) -> Result<Response<ValidatorPerformance>, Status> {
+        let node_id = request.into_inner().node_id;
+        let node = atlas_sdk::utils::NodeId(node_id.clone());
+
+        let (stats, misbehavior_reports, jailed) = self.maestro.cluster.validator_performance(&node).await;
+        Ok(Response::new(ValidatorPerformance {
+            node_id,
+            proposed: stats.proposed,
+            committed: stats.committed,
+            votes_cast: stats.votes_cast,
+            attested: stats.attested,
+            misbehavior_reports,
+            jailed,
+        }))
+    }
+
+    async fn list_proposals(
+        &self,
+        request: Request<ListRequest>,
+    ) -> Result<Response<ListProposalsReply>, Status> {
+        let req = request.into_inner();
+        let page_req = req.page;
+        let mut filter = req.filter.unwrap_or_default();
+        let mask = FieldMask::from_request(page_req.as_ref(), PROPOSAL_FIELDS)?;
+        if !cap_to_finalized(&mut filter, self.maestro.cluster.finalized_height().await) {
+            return Ok(Response::new(ListProposalsReply::default()));
+        }
+        let by_height = match filter.order_by.as_str() {
+            "" | "id" => false,
+            "height" => true,
+            other => return Err(Status::invalid_argument(format!("order_by inválido: {}", other))),
+        };
+
+        let (limit, after) = page_bounds(page_req.as_ref())?;
+        let after = after.as_deref();
+        let status = filter.status();
+
+        // cada fonte entrega só os primeiros `limit + 1` itens após o cursor
+        let mut proposals = Vec::new();
+        if status != ProposalStatus::Committed {
+            let engine = self.maestro.cluster.local_env.engine.lock().await;
+            proposals = pending_candidates(engine.pool.all(), &filter, by_height, after, limit);
+        }
+        {
+            let storage = self.maestro.cluster.local_env.storage.read().await;
+            // a proposta que já foi para o storage conta como commitada
+            proposals.retain(|(_, p, _)| !storage.proposals.iter().any(|c| c.id == p.id));
+            if status != ProposalStatus::Pending {
+                proposals.extend(committed_candidates(&storage, &filter, by_height, after, limit));
             }
         }
+        let page = paginate(proposals, |(key, _, _)| key.as_str(), page_req.as_ref())?;
+
+        Ok(Response::new(ListProposalsReply {
+            proposals: page.items
+                .into_iter()
+                .map(|(_, p, committed)| proposal_record(p, committed, &mask))
+                .collect(),
+            next_cursor: page.next_cursor,
+        }))
+    }
+
+    async fn list_peers(
+        &self,
+        request: Request<ListRequest>,
+    ) -> Result<Response<ListPeersReply>, Status> {
+        let page_req = request.into_inner().page;
+        let mask = FieldMask::from_request(page_req.as_ref(), PEER_FIELDS)?;
+
+        let peers: Vec<PeerRecord> = {
+            let manager = self.maestro.cluster.peer_manager.read().await;
+            manager.known_peers
+                .iter()
+                .map(|(id, node)| PeerRecord {
+                    id: id.0.clone(),
+                    active: manager.active_peers.contains(id),
+                    latency_ms: node.latency.unwrap_or_default(),
+                    reliability_score: node.reliability_score,
+                    capabilities: manager.capabilities.get(id).map(|c| c.names()).unwrap_or_default(),
+                })
+                .collect()
+        };
+        let page = paginate(peers, |p| p.id.as_str(), page_req.as_ref())?;
+
+        Ok(Response::new(ListPeersReply {
+            peers: page.items
+                .into_iter()
+                .map(|mut p| {
+                    if !mask.includes("active") { p.active = false; }
+                    if !mask.includes("latency_ms") { p.latency_ms = 0; }
+                    if !mask.includes("reliability_score") { p.reliability_score = 0.0; }
+                    if !mask.includes("capabilities") { p.capabilities.clear(); }
+                    p
+                })
+                .collect(),
+            next_cursor: page.next_cursor,
+        }))
+    }
+
+    async fn get_node_info(
+        &self,
+        _request: Request<NodeInfoRequest>,
+    ) -> Result<Response<NodeInfo>, Status> {
+        let cluster = &self.maestro.cluster;
+        let node_id = cluster.local_node.read().await.id.0.clone();
+        let height = cluster.local_env.storage.read().await.state_root().height;
+        let network_height = cluster.network_height().await.unwrap_or(height);
+        let last_scrub = cluster.last_scrub.lock().await.as_ref().map(|report| ScrubSummary {
+            proposals_checked: report.proposals_checked as u64,
+            issues: report.issues.iter().map(ToString::to_string).collect(),
+        });
+
+        Ok(Response::new(NodeInfo {
+            node_id,
+            version: version::VERSION.to_string(),
+            git_commit: version::GIT_COMMIT.to_string(),
+            protocol_version: version::PROTOCOL_VERSION,
+            agent_version: version::agent_version(),
+            height,
+            network_height,
+            blocks_behind: network_height.saturating_sub(height),
+            finalized_height: height.saturating_sub(cluster.finality_depth),
+            last_scrub,
+        }))
+    }
+
+    async fn set_faults(
+        &self,
+        request: Request<FaultSettings>,
+    ) -> Result<Response<FaultSettings>, Status> {
+        admin::authorize(
+            &request,
+            "/atlas.ProposalService/SetFaults",
+            &self.maestro.cluster.admin_keys,
+            &*self.maestro.cluster.auth.read().await,
+            &self.admin_nonces,
+        )?;
+
+        #[cfg(feature = "fault-injection")]
+        {
+            let faults = crate::fault::faults();
+            faults.configure(&request.into_inner());
+            Ok(Response::new(faults.settings()))
+        }
+
+        #[cfg(not(feature = "fault-injection"))]
+        {
+            let _ = request;
+            Err(Status::unimplemented("nó compilado sem a feature fault-injection"))
+        }
+    }
+
+    async fn get_stage_latencies(
+        &self,
+        _request: Request<StatsRequest>,
+    ) -> Result<Response<StageLatencies>, Status> {
+        let timings = self.maestro.cluster.timings.lock().await;
+        let stages = Stage::ALL
+            .iter()
+            .filter_map(|stage| {
+                let h = timings.histogram(*stage)?;
+                Some(StageHistogram {
+                    stage: stage.as_str().to_string(),
+                    count: h.count(),
+                    mean_us: h.mean_us(),
+                    p50_us: h.quantile_us(0.5),
+                    p99_us: h.quantile_us(0.99),
+                })
+            })
+            .collect();
+
+        Ok(Response::new(StageLatencies { stages }))
     }
+
+    type SubscribeBlocksStream = EventStream<Block>;
+
+    async fn subscribe_blocks(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeBlocksStream>, Status> {
+        let cluster = Arc::clone(&self.maestro.cluster);
+        // assina antes de ler o storage para não perder commits no meio
+        let events = cluster.stage_events.subscribe();
+        let req = request.into_inner();
+        let finalized = req.commitment() == Commitment::Finalized;
+        let next = match req.from_height {
+            0 if finalized => cluster.finalized_height().await + 1,
+            0 => cluster.local_env.storage.read().await.state_root().height + 1,
+            height => height,
+        };
+
+        // o storage é a fonte dos blocos; os eventos só avisam que há novos,
+        // então um assinante atrasado não perde nada
+        let blocks = stream::unfold(
+            (cluster, events, next, VecDeque::new()),
+            move |(cluster, mut events, mut next, mut pending)| async move {
+                loop {
+                    if let Some(block) = pending.pop_front() {
+                        return Some((Ok(block), (cluster, events, next, pending)));
+                    }
+                    // com FINALIZED, cada commit libera o bloco `finality_depth` abaixo do topo
+                    let to = if finalized { cluster.finalized_height().await } else { u64::MAX };
+                    for (height, proposal, qc) in cluster.blocks_between(next, to).await {
+                        next = height + 1;
+                        pending.push_back(Block {
+                            height,
+                            proposal: Some(proposal_record(proposal, true, &FieldMask::default())),
+                            qc: qc.map(qc_message),
+                        });
+                    }
+                    if !pending.is_empty() {
+                        continue;
+                    }
+                    loop {
+                        match events.recv().await {
+                            Ok((_, Stage::Stored)) | Err(RecvError::Lagged(_)) => break,
+                            Ok(_) => {}
+                            Err(RecvError::Closed) => return None,
+                        }
+                    }
+                }
+            },
+        );
+        Ok(Response::new(Box::pin(blocks)))
+    }
+
+    type SubscribeTransactionsStream = EventStream<ProposalRecord>;
+
+    async fn subscribe_transactions(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeTransactionsStream>, Status> {
+        latest_only(request.get_ref().commitment())?;
+        let cluster = Arc::clone(&self.maestro.cluster);
+        let events = cluster.stage_events.subscribe();
+
+        let transactions = stream::unfold((cluster, events), |(cluster, mut events)| async move {
+            loop {
+                match events.recv().await {
+                    Ok((id, Stage::Pooled)) => {
+                        if let Some((proposal, committed)) = cluster.find_proposal(&id).await {
+                            let record = proposal_record(proposal, committed, &FieldMask::default());
+                            return Some((Ok(record), (cluster, events)));
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(n)) => return Some((Err(lagged(n)), (cluster, events))),
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(transactions)))
+    }
+
+    type SubscribeEventsStream = EventStream<StageEvent>;
+
+    async fn subscribe_events(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        latest_only(request.get_ref().commitment())?;
+        let events = self.maestro.cluster.stage_events.subscribe();
+
+        let stage_events = stream::unfold(events, |mut events| async move {
+            let item = match events.recv().await {
+                Ok((proposal_id, stage)) => Ok(StageEvent { proposal_id, stage: stage.as_str().to_string() }),
+                Err(RecvError::Lagged(n)) => Err(lagged(n)),
+                Err(RecvError::Closed) => return None,
+            };
+            Some((item, events))
+        });
+        Ok(Response::new(Box::pin(stage_events)))
+    }
+}
+
+/// Stream de respostas de uma assinatura.
+type EventStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// Encerra a assinatura de quem não acompanhou o ritmo dos eventos.
+fn lagged(skipped: u64) -> Status {
+    Status::resource_exhausted(format!("assinante atrasado: {} eventos descartados, assine de novo", skipped))
+}
+
+/// Campos aceitos na field mask de propostas (`id` é sempre retornado).
+const PROPOSAL_FIELDS: &[&str] = &[
+    "id", "proposer", "content", "parent", "signature", "public_key", "request_id", "committed",
+];
+
+/// Campos aceitos na field mask de peers (`id` é sempre retornado).
+const PEER_FIELDS: &[&str] = &["id", "active", "latency_ms", "reliability_score", "capabilities"];
+
+fn filter_proposer(filter: &ListFilter, proposal: &Proposal) -> bool {
+    filter.proposer.is_empty() || proposal.proposer.0 == filter.proposer
+}
+
+/// Propostas do pool que entram na próxima página, com a chave de ordenação.
+///
+/// Pendentes não têm altura: ficam de fora da ordenação por altura e de
+/// qualquer faixa de alturas.
+fn pending_candidates(
+    pool: &HashMap<String, Proposal>,
+    filter: &ListFilter,
+    by_height: bool,
+    after: Option<&str>,
+    limit: usize,
+) -> Vec<(String, Proposal, bool)> {
+    if by_height || !in_height_range(filter, None) {
+        return Vec::new();
+    }
+    let mut pending: Vec<&Proposal> = pool
+        .values()
+        .filter(|p| filter_proposer(filter, p) && after.is_none_or(|a| p.id.as_str() > a))
+        .collect();
+    pending.sort_by(|a, b| a.id.cmp(&b.id));
+    pending.into_iter().take(limit + 1).map(|p| (p.id.clone(), p.clone(), false)).collect()
+}
+
+/// Propostas do storage que entram na próxima página, com a chave de ordenação.
+///
+/// Só percorre a cadeia (para calcular alturas) quando a ordenação ou a
+/// faixa de alturas pede, e só clona os itens selecionados.
+fn committed_candidates(
+    storage: &Storage,
+    filter: &ListFilter,
+    by_height: bool,
+    after: Option<&str>,
+    limit: usize,
+) -> Vec<(String, Proposal, bool)> {
+    if by_height {
+        // a cadeia já vem em ordem de altura
+        return storage.chain()
+            .filter(|(h, p)| filter_proposer(filter, p) && in_height_range(filter, Some(*h)))
+            .map(|(h, p)| (height_key(h), p))
+            .filter(|(key, _)| after.is_none_or(|a| key.as_str() > a))
+            .take(limit + 1)
+            .map(|(key, p)| (key, p.clone(), true))
+            .collect();
+    }
+
+    let mut committed: Vec<&Proposal> = if in_height_range(filter, None) {
+        storage.proposals.iter().collect()
+    } else {
+        storage.chain().filter(|(h, _)| in_height_range(filter, Some(*h))).map(|(_, p)| p).collect()
+    };
+    committed.retain(|p| filter_proposer(filter, p) && after.is_none_or(|a| p.id.as_str() > a));
+    committed.sort_by(|a, b| a.id.cmp(&b.id));
+    committed.dedup_by(|a, b| a.id == b.id);
+    committed.into_iter().take(limit + 1).map(|p| (p.id.clone(), p.clone(), true)).collect()
+}
+
+fn proposal_record(proposal: Proposal, committed: bool, mask: &FieldMask) -> ProposalRecord {
+    ProposalRecord {
+        id: proposal.id,
+        proposer: if mask.includes("proposer") { proposal.proposer.0 } else { String::new() },
+        content: if mask.includes("content") { proposal.content } else { String::new() },
+        parent: if mask.includes("parent") { proposal.parent.unwrap_or_default() } else { String::new() },
+        signature: if mask.includes("signature") { proposal.signature.to_vec() } else { Vec::new() },
+        public_key: if mask.includes("public_key") { proposal.public_key } else { Vec::new() },
+        request_id: if mask.includes("request_id") { proposal.request_id.unwrap_or_default() } else { String::new() },
+        committed: mask.includes("committed") && committed,
+        timings: Vec::new(),
+    }
+}
+
+fn qc_message(qc: QcProof) -> QuorumCertificate {
+    QuorumCertificate {
+        proposal_id: qc.proposal_id,
+        votes: qc.votes
+            .into_iter()
+            .map(|v| QcVote {
+                voter: v.voter.0,
+                signature: v.signature.to_vec(),
+                public_key: v.public_key,
+            })
+            .collect(),
+    }
+}
+
+fn write_concern_from(concern: atlas::WriteConcern) -> WriteConcern {
+    match concern {
+        atlas::WriteConcern::Accepted => WriteConcern::Accepted,
+        atlas::WriteConcern::Committed => WriteConcern::Committed,
+        atlas::WriteConcern::Persisted => WriteConcern::Persisted,
+    }
+}
+
+/// Header usado para propagar o ID de rastreamento da requisição.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Tamanho máximo aceito para um `x-request-id` vindo do cliente.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Lê o `x-request-id` enviado pelo cliente ou gera um novo (UUID v4).
+fn request_id_from<T>(request: &Request<T>) -> String {
+    request
+        .metadata()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty() && v.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
 }
 
 // Função para iniciar o servidor gRPC com mTLS.
@@ -65,6 +662,7 @@ pub async fn run_server<P: P2pPublisher + 'static>(
 
     let service = MyProposalService {
         maestro,
+        admin_nonces: admin::NonceCache::default(),
     };
 
     Server::builder()
@@ -75,3 +673,190 @@ pub async fn run_server<P: P2pPublisher + 'static>(
 
     Ok(())
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cluster::core::tests::cluster;
+    use crate::rpc::atlas::{PageRequest, SubscribeRequest};
+    use crate::runtime::maestro::tests::{maestro_with, NullPublisher};
+    use atlas_sdk::{env::consensus::types::ConsensusResult, utils::NodeId};
+
+    fn proposal(id: &str) -> Proposal {
+        Proposal {
+            id: id.to_string(),
+            proposer: NodeId("n1".to_string()),
+            content: "{}".to_string(),
+            parent: None,
+            signature: [0u8; 64],
+            public_key: vec![],
+            request_id: None,
+        }
+    }
+
+    pub(crate) fn commit(storage: &mut Storage, id: &str) {
+        storage.log_proposal(proposal(id));
+        storage.log_result(id, ConsensusResult { proposal_id: id.to_string(), approved: true, votes_received: 1 });
+    }
+
+    /// Percorre todas as páginas de tamanho 1 e devolve as chaves vistas.
+    fn walk(storage: &Storage, pool: &HashMap<String, Proposal>, filter: &ListFilter, by_height: bool) -> Vec<String> {
+        let mut keys = Vec::new();
+        let mut req = PageRequest { limit: 1, ..Default::default() };
+        loop {
+            let (limit, after) = page_bounds(Some(&req)).unwrap();
+            let mut items = pending_candidates(pool, filter, by_height, after.as_deref(), limit);
+            items.extend(committed_candidates(storage, filter, by_height, after.as_deref(), limit));
+            let page = paginate(items, |(key, _, _)| key.as_str(), Some(&req)).unwrap();
+            keys.extend(page.items.into_iter().map(|(key, _, _)| key));
+            if page.next_cursor.is_empty() {
+                return keys;
+            }
+            req.cursor = page.next_cursor;
+        }
+    }
+
+    #[test]
+    fn test_proposal_pages_merge_pool_and_storage() {
+        let mut storage = Storage::new();
+        commit(&mut storage, "c");
+        commit(&mut storage, "a");
+        let pool = HashMap::from([("b".to_string(), proposal("b"))]);
+
+        assert_eq!(walk(&storage, &pool, &ListFilter::default(), false), ["a", "b", "c"]);
+        assert_eq!(walk(&storage, &pool, &ListFilter::default(), true), [height_key(1), height_key(2)]);
+
+        let ranged = ListFilter { from_height: 2, ..Default::default() };
+        assert_eq!(walk(&storage, &pool, &ranged, false), ["a"]);
+    }
+
+    /// Nó com p1..p3 commitadas (alturas 1 a 3) e `finality_depth` 2: só p1 está finalizada.
+    pub(crate) async fn finality_node() -> Cluster {
+        let mut node = cluster([]);
+        node.finality_depth = 2;
+        {
+            let mut storage = node.local_env.storage.write().await;
+            for id in ["p1", "p2", "p3"] {
+                commit(&mut storage, id);
+                storage.log_certificate(QcProof::new(id, Vec::new()));
+            }
+        }
+        node
+    }
+
+    fn service(node: Cluster) -> MyProposalService<NullPublisher> {
+        MyProposalService { maestro: Arc::new(maestro_with(node)), admin_nonces: admin::NonceCache::default() }
+    }
+
+    fn query(id: &str, commitment: Commitment) -> Request<ProposalQuery> {
+        Request::new(ProposalQuery { proposal_id: id.to_string(), commitment: commitment as i32 })
+    }
+
+    fn list(commitment: Commitment) -> Request<ListRequest> {
+        Request::new(ListRequest {
+            page: None,
+            filter: Some(ListFilter { commitment: commitment as i32, ..Default::default() }),
+        })
+    }
+
+    fn subscribe(from_height: u64, commitment: Commitment) -> Request<SubscribeRequest> {
+        Request::new(SubscribeRequest { from_height, commitment: commitment as i32 })
+    }
+
+    #[tokio::test]
+    async fn test_ensure_finalized_waits_for_finality_depth() {
+        let node = finality_node().await;
+
+        // altura 1 está a 2 do topo (3): finalizada; a 2 ainda não
+        assert!(ensure_finalized(&node, "p1", Commitment::Finalized).await.is_ok());
+        let below = ensure_finalized(&node, "p2", Commitment::Finalized).await.unwrap_err();
+        assert_eq!(below.code(), tonic::Code::FailedPrecondition);
+        assert!(ensure_finalized(&node, "p3", Commitment::Latest).await.is_ok());
+
+        let missing = ensure_finalized(&node, "nope", Commitment::Finalized).await.unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::FailedPrecondition);
+    }
+
+    #[tokio::test]
+    async fn test_proposal_queries_honor_commitment() {
+        let svc = service(finality_node().await);
+
+        for (commitment, served) in [(Commitment::Latest, true), (Commitment::Finalized, false)] {
+            assert_eq!(svc.get_proposal(query("p2", commitment)).await.is_ok(), served);
+            assert_eq!(svc.get_result(query("p2", commitment)).await.is_ok(), served);
+            assert_eq!(svc.get_proposal_with_qc(query("p2", commitment)).await.is_ok(), served);
+            assert_eq!(svc.list_votes(query("p2", commitment)).await.is_ok(), served);
+
+            assert!(svc.get_proposal(query("p1", commitment)).await.is_ok());
+            assert!(svc.get_result(query("p1", commitment)).await.is_ok());
+            assert!(svc.get_proposal_with_qc(query("p1", commitment)).await.is_ok());
+            assert!(svc.list_votes(query("p1", commitment)).await.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_listings_honor_commitment() {
+        let node = finality_node().await;
+        node.local_env.engine.lock().await.add_proposal(proposal("pending"));
+        let svc = service(node);
+
+        let ids = |reply: ListProposalsReply| reply.proposals.into_iter().map(|p| p.id).collect::<Vec<_>>();
+        let latest = svc.list_proposals(list(Commitment::Latest)).await.unwrap().into_inner();
+        assert_eq!(ids(latest), ["p1", "p2", "p3", "pending"]);
+        let finalized = svc.list_proposals(list(Commitment::Finalized)).await.unwrap().into_inner();
+        assert_eq!(ids(finalized), ["p1"]);
+
+        let heights = |reply: ListAttestationsReply| reply.attestations.into_iter().map(|a| a.height).collect::<Vec<_>>();
+        assert_eq!(heights(svc.list_attestations(list(Commitment::Latest)).await.unwrap().into_inner()), [1, 2, 3]);
+        assert_eq!(heights(svc.list_attestations(list(Commitment::Finalized)).await.unwrap().into_inner()), [1]);
+
+        let mut nothing_final = ListFilter { commitment: Commitment::Finalized as i32, ..Default::default() };
+        assert!(!cap_to_finalized(&mut nothing_final, 0));
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_honor_commitment() {
+        use futures::StreamExt;
+        use std::time::Duration;
+
+        let svc = service(finality_node().await);
+
+        let mut latest = svc.subscribe_blocks(subscribe(1, Commitment::Latest)).await.unwrap().into_inner();
+        for height in 1..=3 {
+            assert_eq!(latest.next().await.unwrap().unwrap().height, height);
+        }
+
+        let mut finalized = svc.subscribe_blocks(subscribe(1, Commitment::Finalized)).await.unwrap().into_inner();
+        assert_eq!(finalized.next().await.unwrap().unwrap().height, 1);
+        assert!(tokio::time::timeout(Duration::from_millis(50), finalized.next()).await.is_err());
+
+        // um novo commit finaliza a altura 2
+        commit(&mut *svc.maestro.cluster.local_env.storage.write().await, "p4");
+        svc.maestro.cluster.mark_stage("p4", Stage::Stored).await;
+        assert_eq!(finalized.next().await.unwrap().unwrap().height, 2);
+
+        assert!(svc.subscribe_transactions(subscribe(0, Commitment::Latest)).await.is_ok());
+        let refused = svc.subscribe_transactions(subscribe(0, Commitment::Finalized)).await.err().unwrap();
+        assert_eq!(refused.code(), tonic::Code::InvalidArgument);
+        assert!(svc.subscribe_events(subscribe(0, Commitment::Finalized)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_node_info_reports_last_scrub() {
+        let node = cluster([]);
+        // resultado sem votos no log e proposta sem assinatura válida
+        commit(&mut *node.local_env.storage.write().await, "p1");
+        let svc = service(node);
+        let info = || svc.get_node_info(Request::new(NodeInfoRequest {}));
+        assert!(info().await.unwrap().into_inner().last_scrub.is_none());
+
+        let cluster = &svc.maestro.cluster;
+        cluster.scrub_storage().await.unwrap();
+        let scrub = info().await.unwrap().into_inner().last_scrub.unwrap();
+        assert_eq!(scrub.proposals_checked, 1);
+        assert_eq!(scrub.issues.len(), 2, "{:?}", scrub.issues);
+
+        cluster.refresh_metrics().await;
+        assert_eq!(cluster.metrics.scrub_issues.get(), 2);
+    }
+}
@@ -60,7 +60,9 @@ pub async fn build_runtime(
     grpc_addr: std::net::SocketAddr,
 ) -> Result<AtlasRuntime> {
     let config = Config::load_from_file(config_path)?;
-    let cluster = Arc::new(config.build_cluster_env(auth));
+    let mut cluster = config.build_cluster_env(auth);
+    cluster.data_dir = p2p_cfg.network.data_dir();
+    let cluster = Arc::new(cluster);
 
     // 2) Canais P2P
     let (adapter_evt_tx, maestro_evt_rx) = mpsc::channel::<AdapterEvent>(64);
@@ -77,6 +79,16 @@ pub async fn build_runtime(
 
     tokio::spawn(async move { adapter.run().await });
 
+    // 3.1) Métricas: em todos os nós, não só no líder
+    if let Some(addr) = cluster.metrics_addr {
+        let c = Arc::clone(&cluster);
+        tokio::spawn(async move {
+            if let Err(e) = crate::metrics::run_server(c, addr).await {
+                eprintln!("Erro no servidor de métricas: {}", e);
+            }
+        });
+    }
+
     // 4) Porta (publisher) e Maestro
     let publisher = AdapterHandle { cmd_tx: maestro_cmd_tx };
     let maestro = Maestro {
@@ -115,6 +127,8 @@ pub async fn run_cli() -> Result<()> {
         enable_mdns: true,
         enable_kademlia: true,
         keypair_path,
+        network: Default::default(),
+        capabilities: Default::default(),
     };
 
     let grpc_addr = "0.0.0.0:50051".parse().unwrap();
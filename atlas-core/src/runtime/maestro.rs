@@ -1,13 +1,61 @@
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, Mutex};
 use tokio::task::JoinHandle;
 use tokio::time::{self, Duration};
 use tracing::info;
-use crate::network::p2p::{ports::P2pPublisher, adapter::AdapterCmd, events::AdapterEvent};
-use crate::cluster::core::Cluster;
+use crate::network::p2p::{ports::P2pPublisher, adapter::AdapterCmd, envelope, events::AdapterEvent, protocol::Heartbeat};
+use atlas_sdk::env::proposal::SealedProposal;
+use crate::cluster::{core::Cluster, watchdog::STALL_TIMEOUT};
+use crate::env::timing::Stage;
 use crate::rpc;
 
+/// Intervalo entre varreduras de integridade do storage (baixa prioridade).
+const SCRUB_INTERVAL_SECS: u64 = 300;
+
+/// Intervalo entre passadas do zelador de retenção (logs e dumps antigos).
+const RETENTION_INTERVAL_SECS: u64 = 3600;
+
+/// Tempo sem sinal do líder antes de pedir uma troca de view.
+const LEADER_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Prazo padrão para uma submissão atingir o write concern pedido.
+pub const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maior prazo aceito de um cliente para atingir o write concern.
+pub const MAX_WRITE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Momento em que uma submissão externa é confirmada ao cliente.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WriteConcern {
+    /// Assinada, no pool local e publicada aos peers.
+    #[default]
+    Accepted,
+    /// Aprovada pelo quórum e gravada no storage.
+    Committed,
+    /// Gravada e sincronizada no arquivo de auditoria.
+    Persisted,
+}
+
+impl WriteConcern {
+    /// Etapa do caminho de escrita que satisfaz o write concern.
+    fn stage(self) -> Option<Stage> {
+        match self {
+            WriteConcern::Accepted => None,
+            WriteConcern::Committed => Some(Stage::Stored),
+            WriteConcern::Persisted => Some(Stage::Persisted),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WriteConcern::Accepted => "accepted",
+            WriteConcern::Committed => "committed",
+            WriteConcern::Persisted => "persisted",
+        }
+    }
+}
+
 
 pub struct Maestro<P: P2pPublisher> {
     pub cluster: Arc<Cluster>,
@@ -22,8 +70,15 @@ use crate::env::proposal::Proposal;
 
 impl<P: P2pPublisher + 'static> Maestro<P> {
     /// Cria e submete uma proposta vinda de uma fonte externa (ex: gRPC).
-    pub async fn submit_external_proposal(&self, content: String) -> Result<String, String> {
+    #[tracing::instrument(name = "submit", skip_all, fields(request_id = %request_id, proposal_id = tracing::field::Empty))]
+    pub async fn submit_external_proposal(&self, content: String, request_id: String) -> Result<String, String> {
+        if self.cluster.is_halted() {
+            return Err("Produção de propostas suspensa: divergência de estado detectada".to_string());
+        }
+
         let id = format!("prop-{}", rand::random::<u64>());
+        tracing::Span::current().record("proposal_id", id.as_str());
+        self.cluster.mark_stage(&id, Stage::Ingest).await;
         let local_node = self.cluster.local_node.read().await;
         let proposer = local_node.id.clone();
         let public_key = self.cluster.auth.read().await.public_key().to_vec();
@@ -35,29 +90,34 @@ impl<P: P2pPublisher + 'static> Maestro<P> {
             parent: None,
             signature: [0u8; 64],
             public_key,
+            request_id: Some(request_id),
         };
 
         // Use standardized signing bytes (bincode of ProposalSignView)
         let msg = atlas_sdk::env::proposal::signing_bytes(&proposal);
-        let signature_vec = self.cluster.auth.read().await.sign(msg).map_err(|e| e.to_string())?;
+        let signature_vec = self.cluster.auth.read().await.sign(msg.clone()).map_err(|e| e.to_string())?;
         
         if signature_vec.len() == 64 {
             proposal.signature.copy_from_slice(&signature_vec);
+            self.cluster.mark_stage(&proposal.id, Stage::Signed).await;
             info!("✅ Proposta assinada com sucesso! ID: {}", proposal.id);
-            tracing::info!(target: "consensus", "EVENT:PROPOSE id={} proposer={}", proposal.id, proposal.proposer);
+            tracing::info!(target: "consensus", "EVENT:PROPOSE id={} proposer={} request_id={}", proposal.id, proposal.proposer, proposal.request_id.as_deref().unwrap_or("-"));
         } else {
             return Err(format!("Invalid signature length: {}", signature_vec.len()));
         }
         let proposal_id = proposal.id.clone();
+        // a assinatura não cobre a si mesma: os bytes assinados continuam valendo
+        let sealed = SealedProposal::with_signing_bytes(proposal, msg);
 
         // Chame o cluster para processar a proposta e retornar um comando de rede.
-        let cmd = self.cluster.submit_proposal(proposal).await.map_err(|e| e.to_string())?;
+        let cmd = self.cluster.submit_proposal(sealed).await.map_err(|e| e.to_string())?;
 
         // Despache o comando para a camada de rede usando o publicador P2P.
         match cmd {
             AdapterCmd::Publish { topic, data } => {
                 info!("Disseminando proposta externa via P2P...");
-                self.p2p.publish(&topic, data).await.map_err(|e| e.to_string())?
+                self.p2p.publish(&topic, data).await.map_err(|e| e.to_string())?;
+                self.cluster.mark_stage(&proposal_id, Stage::Published).await;
             }
             _ => {
                 return Err(
@@ -69,9 +129,74 @@ impl<P: P2pPublisher + 'static> Maestro<P> {
         Ok(proposal_id)
     }
 
+    /// Espera a proposta `id` atingir `concern`; `false` se o prazo acabar antes.
+    ///
+    /// `commits` deve ser assinado antes da submissão, para não perder um
+    /// commit rápido. O prazo é limitado a `MAX_WRITE_TIMEOUT`.
+    pub async fn await_write_concern(
+        &self,
+        mut commits: broadcast::Receiver<(String, Stage)>,
+        id: &str,
+        concern: WriteConcern,
+        timeout: Duration,
+    ) -> bool {
+        let Some(target) = concern.stage() else { return true };
+
+        let wait = async {
+            loop {
+                match commits.recv().await {
+                    Ok((committed, stage)) if committed == id && stage == target => return true,
+                    Ok(_) => {}
+                    // eventos perdidos: o estágio pode ter passado entre eles
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        if self.cluster.reached_stage(id, target).await {
+                            return true;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return false,
+                }
+            }
+        };
+        time::timeout(timeout.min(MAX_WRITE_TIMEOUT), wait).await.unwrap_or(false)
+    }
+
+    /// Pede e divulga a troca para a próxima view.
+    async fn start_view_change(&self) {
+        match self.cluster.request_view_change().await {
+            Ok(Some(vc)) => {
+                if let Err(e) = self.p2p.publish("atlas/view/v1", vc.bytes()).await {
+                    eprintln!("Erro ao publicar troca de view: {}", e);
+                }
+                // o voto local pode ter completado o quórum
+                self.cluster.elect_leader().await;
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("request_view_change erro: {e}"),
+        }
+    }
+
+    /// Atualiza o heartbeat com o state root atual.
+    async fn announce_state(&self) {
+        let hb = self.cluster.heartbeat().await;
+        match bincode::serialize(&hb) {
+            Ok(bytes) => {
+                if let Err(e) = self.p2p.set_heartbeat(bytes).await {
+                    eprintln!("Erro ao atualizar heartbeat: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Erro ao serializar heartbeat: {}", e),
+        }
+    }
+
     pub async fn run(self: Arc<Self>) {
         info!("[MAESTRO DEBUG] Tarefa Maestro::run iniciada.");
         let mut election_timer = time::interval(Duration::from_secs(5));
+        let mut scrub_timer = time::interval(Duration::from_secs(SCRUB_INTERVAL_SECS));
+        let mut view_timer = time::interval(LEADER_TIMEOUT / 3);
+        let mut retention_timer = time::interval(Duration::from_secs(RETENTION_INTERVAL_SECS));
+        let mut watchdog_timer = time::interval(STALL_TIMEOUT / 4);
+        scrub_timer.tick().await; // o primeiro tick é imediato; a varredura começa após o intervalo
+        self.announce_state().await;
 
         info!("[MAESTRO DEBUG] Entrando no loop principal.");
         loop {
@@ -79,10 +204,20 @@ impl<P: P2pPublisher + 'static> Maestro<P> {
                 res = self.evt_rx.lock() => {
                     let mut guard = res;
                     if let Some(evt) = guard.recv().await {
+                        self.cluster.metrics.record_network_event(&evt);
                         // Processar o evento de rede
                         match evt {
                             AdapterEvent::Proposal(bytes) => {
-                                if let Err(e) = self.cluster.handle_proposal(bytes).await {
+                                // decodifica uma vez só; aceita também o layout antigo durante upgrades
+                                let sealed = match envelope::decode_sealed(bytes) {
+                                    Ok(sealed) => sealed,
+                                    Err(e) => {
+                                        eprintln!("handle_proposal_bytes erro: {e}");
+                                        continue;
+                                    }
+                                };
+                                self.cluster.note_leader_activity(&sealed.proposal().proposer).await;
+                                if let Err(e) = self.cluster.handle_proposal(sealed).await {
                                     eprintln!("handle_proposal_bytes erro: {e}");
                                     continue;
                                 }
@@ -100,7 +235,13 @@ impl<P: P2pPublisher + 'static> Maestro<P> {
                             }
     
                             AdapterEvent::Vote(bytes) => {
-                                if let Err(e) = self.cluster.handle_vote(bytes).await {
+                                let handled = self.cluster.handle_vote(bytes).await;
+                                if let Ok(Some(report)) = &handled {
+                                    if let Err(e) = self.p2p.publish("atlas/misbehavior/v1", report.bytes()).await {
+                                        eprintln!("Erro ao publicar relatório de mau comportamento: {}", e);
+                                    }
+                                }
+                                if let Err(e) = handled {
                                     eprintln!("handle_vote_bytes erro: {e}");
                                 } else {
                                     // Check for consensus after receiving a vote
@@ -110,9 +251,16 @@ impl<P: P2pPublisher + 'static> Maestro<P> {
                                                 if result.approved {
                                                     info!("🎉 Proposta APROVADA: {}", result.proposal_id);
                                                     tracing::info!(target: "consensus", "EVENT:COMMIT id={} votes={}", result.proposal_id, result.votes_received);
+                                                    self.cluster.mark_stage(&result.proposal_id, Stage::Approved).await;
                                                     
-                                                    if let Err(e) = self.cluster.commit_proposal(result).await {
-                                                        eprintln!("Erro ao commitar proposta: {}", e);
+                                                    let id = result.proposal_id.clone();
+                                                    match self.cluster.commit_proposal(result).await {
+                                                        Ok(()) => self.announce_state().await,
+                                                        Err(e) => {
+                                                            // sem persistência não anunciamos o novo estado aos peers
+                                                            eprintln!("Erro ao commitar proposta: {}", e);
+                                                            tracing::error!(target: "consensus", "EVENT:COMMIT_FAIL id={} error={}", id, e);
+                                                        }
                                                     }
                                                 }
                                             }
@@ -125,6 +273,15 @@ impl<P: P2pPublisher + 'static> Maestro<P> {
                             AdapterEvent::Heartbeat{from, data} => {
                                 info!("❤️ HB de {from} ({:?} bytes)", data.len());
                                 tracing::debug!("❤️ HB de {from} ({:?} bytes)", data.len());
+                                self.cluster.note_leader_activity(&from).await;
+
+                                // heartbeats antigos ("hi from adapter") não trazem state root
+                                if let Ok(hb) = bincode::deserialize::<Heartbeat>(&data) {
+                                    self.cluster.track_peer_height(&from, hb.state.height).await;
+                                    if let Err(e) = self.cluster.check_peer_state(&from, &hb).await {
+                                        eprintln!("check_peer_state erro: {e}");
+                                    }
+                                }
                                 
                                 // Update peer stats
                                 let node = crate::cluster::node::Node::new(from.clone(), "".to_string(), None, 0.0);
@@ -133,6 +290,20 @@ impl<P: P2pPublisher + 'static> Maestro<P> {
                                 );
                             }
 
+                            AdapterEvent::ViewChange(bytes) => {
+                                match self.cluster.handle_view_change(bytes).await {
+                                    Ok(true) => self.cluster.elect_leader().await,
+                                    Ok(false) => {}
+                                    Err(e) => eprintln!("handle_view_change erro: {e}"),
+                                }
+                            }
+
+                            AdapterEvent::Misbehavior(bytes) => {
+                                if let Err(e) = self.cluster.handle_misbehavior_report(bytes).await {
+                                    eprintln!("handle_misbehavior_report erro: {e}");
+                                }
+                            }
+
                             AdapterEvent::PeerDiscovered(id) => {
                                 info!("🔍 Peer descoberto: {}", id);
                                 let node = crate::cluster::node::Node::new(id.clone(), "".to_string(), None, 0.0);
@@ -154,8 +325,45 @@ impl<P: P2pPublisher + 'static> Maestro<P> {
                     }
                 },
 
+                _ = scrub_timer.tick() => {
+                    if let Err(e) = self.cluster.scrub_storage().await {
+                        eprintln!("scrub_storage erro: {e}");
+                    }
+                }
+
+                _ = retention_timer.tick() => {
+                    match self.cluster.retention.enforce(&self.cluster.data_dir, self.cluster.clock.now()) {
+                        Ok(report) => {
+                            if report.removed_files > 0 {
+                                info!("🧹 Retenção: {} arquivos removidos ({} bytes)", report.removed_files, report.removed_bytes);
+                            }
+                            tracing::info!(target: "consensus", "EVENT:RETENTION removed={} freed_bytes={} retained_bytes={}", report.removed_files, report.removed_bytes, report.retained_bytes);
+                        }
+                        Err(e) => eprintln!("retenção erro: {e}"),
+                    }
+                }
+
+                _ = view_timer.tick() => {
+                    if self.cluster.leader_timed_out(LEADER_TIMEOUT).await {
+                        self.start_view_change().await;
+                    }
+                }
+
+                _ = watchdog_timer.tick() => {
+                    // líder vivo mas sem commits: troca a view mesmo assim
+                    if self.cluster.check_stall(STALL_TIMEOUT).await {
+                        self.start_view_change().await;
+                    }
+                }
+
                 _ = election_timer.tick() => {
                     info!("[MAESTRO DEBUG] Timer da eleição disparou.");
+                    // redivulga o último voto de view, para nós reiniciados alcançarem o cluster
+                    if let Some(vc) = self.cluster.view_vote().await {
+                        if let Err(e) = self.p2p.publish("atlas/view/v1", vc.bytes()).await {
+                            eprintln!("Erro ao publicar troca de view: {}", e);
+                        }
+                    }
                     self.cluster.elect_leader().await;
 
                     // Bloco para isolar os borrows e evitar conflitos de ownership
@@ -174,9 +382,21 @@ impl<P: P2pPublisher + 'static> Maestro<P> {
                     if am_i_leader && !server_running {
                         info!("Este nó é o líder. Iniciando servidor gRPC...");
                         let maestro_clone = Arc::clone(&self);
+                        let jsonrpc_addr = self.cluster.jsonrpc_addr;
+                        // os dois servidores vivem na mesma task, para pararem juntos
                         let server_task = tokio::spawn(async move {
-                            if let Err(e) = rpc::server::run_server(maestro_clone, grpc_addr_copy).await {
-                                eprintln!("Erro no servidor gRPC: {}", e);
+                            let jsonrpc = async {
+                                let Some(addr) = jsonrpc_addr else { return Ok(()) };
+                                rpc::jsonrpc::run_server(Arc::clone(&maestro_clone), addr).await
+                                    .map_err(|e| format!("Erro no servidor JSON-RPC: {}", e))
+                            };
+                            let grpc = async {
+                                rpc::server::run_server(Arc::clone(&maestro_clone), grpc_addr_copy).await
+                                    .map_err(|e| format!("Erro no servidor gRPC: {}", e))
+                            };
+                            // a falha de qualquer um encerra a task (e derruba o outro)
+                            if let Err(e) = tokio::try_join!(grpc, jsonrpc) {
+                                eprintln!("{}", e);
                             }
                         });
                         *handle_guard = Some(server_task);
@@ -191,3 +411,82 @@ impl<P: P2pPublisher + 'static> Maestro<P> {
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::cluster::core::{tests::cluster, STAGE_EVENTS_CAPACITY};
+
+    /// Publicador que descarta tudo, para testes sem rede.
+    pub(crate) struct NullPublisher;
+
+    #[async_trait::async_trait]
+    impl P2pPublisher for NullPublisher {
+        async fn publish(&self, _topic: &str, _data: Vec<u8>) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn maestro() -> Maestro<NullPublisher> {
+        maestro_with(cluster([]))
+    }
+
+    /// Maestro sem rede em volta de `cluster`.
+    pub(crate) fn maestro_with(cluster: Cluster) -> Maestro<NullPublisher> {
+        Maestro {
+            cluster: Arc::new(cluster),
+            p2p: NullPublisher,
+            evt_rx: Mutex::new(mpsc::channel(1).1),
+            grpc_addr: "127.0.0.1:0".parse().unwrap(),
+            grpc_server_handle: Mutex::new(None),
+        }
+    }
+
+    const SHORT: Duration = Duration::from_millis(50);
+
+    #[tokio::test]
+    async fn test_accepted_does_not_wait() {
+        let m = maestro();
+        let events = m.cluster.stage_events.subscribe();
+        assert!(m.await_write_concern(events, "p1", WriteConcern::Accepted, Duration::ZERO).await);
+    }
+
+    #[tokio::test]
+    async fn test_committed_and_persisted_wait_for_their_stage() {
+        let m = maestro();
+        let events = m.cluster.stage_events.subscribe();
+        m.cluster.mark_stage("p1", Stage::Stored).await;
+        assert!(m.await_write_concern(events, "p1", WriteConcern::Committed, SHORT).await);
+
+        let events = m.cluster.stage_events.subscribe();
+        m.cluster.mark_stage("p1", Stage::Stored).await;
+        assert!(!m.await_write_concern(events, "p1", WriteConcern::Persisted, SHORT).await);
+
+        let events = m.cluster.stage_events.subscribe();
+        m.cluster.mark_stage("p1", Stage::Persisted).await;
+        assert!(m.await_write_concern(events, "p1", WriteConcern::Persisted, SHORT).await);
+    }
+
+    #[tokio::test]
+    async fn test_times_out_when_the_stage_never_comes() {
+        let m = maestro();
+        let events = m.cluster.stage_events.subscribe();
+        m.cluster.mark_stage("other", Stage::Stored).await;
+        assert!(!m.await_write_concern(events, "p1", WriteConcern::Committed, SHORT).await);
+    }
+
+    #[tokio::test]
+    async fn test_lagged_receiver_rechecks_the_stage() {
+        let m = maestro();
+        for concern in [WriteConcern::Committed, WriteConcern::Persisted] {
+            let id = format!("p-{}", concern.as_str());
+            let events = m.cluster.stage_events.subscribe();
+            m.cluster.mark_stage(&id, concern.stage().unwrap()).await;
+            // o evento da proposta é descartado do canal antes de ser lido
+            for _ in 0..=STAGE_EVENTS_CAPACITY {
+                m.cluster.mark_stage("other", Stage::Received).await;
+            }
+            assert!(m.await_write_concern(events, &id, concern, SHORT).await, "{}", concern.as_str());
+        }
+    }
+}
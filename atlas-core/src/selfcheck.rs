@@ -0,0 +1,194 @@
+//! selfcheck.rs
+//!
+//! Startup self-checks.
+//!
+//! Before joining consensus the node verifies what it will depend on:
+//! a writable data directory with free space, a sane clock, a usable
+//! keypair, a loadable config whose storage passes a scrub, and free
+//! listening ports. The outcome is written as `startup-report.json` in
+//! the data directory; any failed check aborts startup rather than letting
+//! the node limp into consensus.
+
+use std::fs;
+use std::net::{SocketAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::{config::Config, network::key_manager, version};
+
+/// Free space below which the data directory check fails.
+pub const MIN_FREE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Clocks reading earlier than this (2024-01-01T00:00:00Z) are considered unset.
+const EARLIEST_SANE_TIME: Duration = Duration::from_secs(1_704_067_200);
+
+/// Name of the report file written to the data directory.
+pub const REPORT_FILE: &str = "startup-report.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    /// Worth a look, but the node can start.
+    Warn,
+    /// The node must not start.
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+/// Outcome of every startup check.
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupReport {
+    pub version: String,
+    pub passed: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+/// What the checks inspect.
+#[derive(Debug, Clone)]
+pub struct SelfCheckInputs {
+    pub data_dir: PathBuf,
+    pub config_path: PathBuf,
+    pub keypair_path: PathBuf,
+    pub grpc_addr: SocketAddr,
+    /// P2P listen multiaddr, e.g. `/ip4/0.0.0.0/tcp/4001`.
+    pub p2p_listen: String,
+}
+
+fn result(name: &'static str, outcome: Result<String, (CheckStatus, String)>) -> CheckResult {
+    match outcome {
+        Ok(detail) => CheckResult { name, status: CheckStatus::Ok, detail },
+        Err((status, detail)) => CheckResult { name, status, detail },
+    }
+}
+
+fn fail(detail: impl ToString) -> (CheckStatus, String) {
+    (CheckStatus::Fail, detail.to_string())
+}
+
+/// Runs every check.
+pub fn run(inputs: &SelfCheckInputs) -> StartupReport {
+    let checks = vec![
+        result("data_dir", check_data_dir(&inputs.data_dir)),
+        result("disk_space", check_disk_space(&inputs.data_dir)),
+        result("clock", check_clock(SystemTime::now())),
+        result("keypair", check_keypair(&inputs.keypair_path)),
+        result("config", check_config(&inputs.config_path)),
+        result("grpc_port", check_port(inputs.grpc_addr)),
+        result("p2p_port", p2p_addr(&inputs.p2p_listen).map_or_else(
+            || Err((CheckStatus::Warn, format!("not a TCP multiaddr, not checked: {}", inputs.p2p_listen))),
+            check_port,
+        )),
+    ];
+
+    StartupReport {
+        version: version::agent_version(),
+        passed: checks.iter().all(|c| c.status != CheckStatus::Fail),
+        checks,
+    }
+}
+
+impl StartupReport {
+    /// Writes the report as JSON to [`REPORT_FILE`] in `data_dir`.
+    pub fn save(&self, data_dir: &Path) -> std::io::Result<PathBuf> {
+        let path = data_dir.join(REPORT_FILE);
+        fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        Ok(path)
+    }
+}
+
+fn check_data_dir(dir: &Path) -> Result<String, (CheckStatus, String)> {
+    fs::create_dir_all(dir).map_err(fail)?;
+    let probe = dir.join(".write-probe");
+    fs::write(&probe, b"ok").map_err(fail)?;
+    let _ = fs::remove_file(&probe);
+    Ok(format!("{} is writable", dir.display()))
+}
+
+fn check_disk_space(dir: &Path) -> Result<String, (CheckStatus, String)> {
+    let free = fs2::available_space(dir).map_err(fail)?;
+    if free < MIN_FREE_BYTES {
+        return Err(fail(format!("{} bytes free, need at least {}", free, MIN_FREE_BYTES)));
+    }
+    Ok(format!("{} bytes free", free))
+}
+
+fn check_clock(now: SystemTime) -> Result<String, (CheckStatus, String)> {
+    let since_epoch = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+    if since_epoch < EARLIEST_SANE_TIME {
+        return Err(fail(format!("system clock reads {}s since the epoch; is it set?", since_epoch.as_secs())));
+    }
+    Ok(format!("{}s since the epoch", since_epoch.as_secs()))
+}
+
+fn check_keypair(path: &Path) -> Result<String, (CheckStatus, String)> {
+    if !path.exists() {
+        return Err((CheckStatus::Warn, format!("{} not found; a new identity will be generated", path.display())));
+    }
+    let keypair = key_manager::load_or_generate_keypair(path).map_err(fail)?;
+    keypair.try_into_ed25519().map_err(|_| fail("keypair is not ed25519"))?;
+    Ok(format!("{} loaded", path.display()))
+}
+
+fn check_config(path: &Path) -> Result<String, (CheckStatus, String)> {
+    let config = Config::load_from_file(&path.to_string_lossy()).map_err(fail)?;
+    let report = config.storage.scrub();
+    if !report.is_clean() {
+        return Err((CheckStatus::Warn, format!("storage scrub found {} issues", report.issues.len())));
+    }
+    Ok(format!("{} proposals, scrub clean", report.proposals_checked))
+}
+
+fn check_port(addr: SocketAddr) -> Result<String, (CheckStatus, String)> {
+    TcpListener::bind(addr).map_err(|e| fail(format!("{}: {}", addr, e)))?;
+    Ok(format!("{} is free", addr))
+}
+
+/// Extracts `ip:port` from a `/ip4/<ip>/tcp/<port>` multiaddr.
+fn p2p_addr(multiaddr: &str) -> Option<SocketAddr> {
+    match multiaddr.split('/').collect::<Vec<_>>().as_slice() {
+        ["", "ip4", ip, "tcp", port, ..] => format!("{}:{}", ip, port).parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_fails_on_missing_config_and_busy_port() {
+        let dir = tempfile::tempdir().unwrap();
+        let busy = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let report = run(&SelfCheckInputs {
+            data_dir: dir.path().join("data"),
+            config_path: dir.path().join("missing.json"),
+            keypair_path: dir.path().join("keys/keypair"),
+            grpc_addr: busy.local_addr().unwrap(),
+            p2p_listen: "/ip4/127.0.0.1/tcp/0".into(),
+        });
+
+        let status = |name| report.checks.iter().find(|c| c.name == name).unwrap().status;
+        assert!(!report.passed);
+        assert_eq!(status("data_dir"), CheckStatus::Ok);
+        assert_eq!(status("keypair"), CheckStatus::Warn);
+        assert_eq!(status("config"), CheckStatus::Fail);
+        assert_eq!(status("grpc_port"), CheckStatus::Fail);
+        assert_eq!(status("p2p_port"), CheckStatus::Ok);
+    }
+
+    #[test]
+    fn test_clock_before_2024_fails() {
+        assert!(check_clock(UNIX_EPOCH).is_err());
+        assert!(check_clock(UNIX_EPOCH + EARLIEST_SANE_TIME + Duration::from_secs(1)).is_ok());
+    }
+}
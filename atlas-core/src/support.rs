@@ -0,0 +1,212 @@
+//! support.rs
+//!
+//! Support bundles (`atlas-core support-bundle`).
+//!
+//! Collects what is usually asked for in a bug report — version, sanitized
+//! config, peer table, storage tip, the last startup report and the tail of
+//! the node logs — into a single `.tar.gz`. Everything is read from the
+//! node's data directory, so the bundle can be produced while the node is
+//! down. Key material is never read: the keypair file is skipped and any
+//! config field that looks like a secret is redacted.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::{write::GzEncoder, Compression};
+use serde_json::{json, Value};
+
+use crate::{config::Config, env::storage::{audit::load_audit, Storage}, selfcheck, version};
+
+/// Number of trailing lines kept from each log file.
+pub const LOG_TAIL_LINES: usize = 2000;
+
+/// Placeholder written in place of redacted values.
+pub const REDACTED: &str = "<redacted>";
+
+/// Field names (or fragments) whose values are never written to a bundle.
+const SECRET_FIELDS: &[&str] = &["secret", "private", "seed", "password", "token", "keypair", "mnemonic"];
+
+/// Where the bundle contents are read from.
+#[derive(Debug, Clone)]
+pub struct BundleSources {
+    /// Node config file.
+    pub config_path: PathBuf,
+    /// Network data directory (holds `logs/` and the audit files).
+    pub data_dir: PathBuf,
+}
+
+/// Replaces every value stored under a secret-looking key with [`REDACTED`].
+pub fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                if SECRET_FIELDS.iter().any(|s| key.contains(s)) {
+                    *v = Value::String(REDACTED.to_string());
+                } else {
+                    redact(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Writes a support bundle to `out` and returns the names of the files it holds.
+///
+/// Missing sources (no config, no logs yet) are recorded in `errors.txt`
+/// instead of failing the whole bundle.
+pub fn write_bundle(sources: &BundleSources, out: &Path) -> io::Result<Vec<String>> {
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut errors = Vec::new();
+
+    files.push(("version.txt".into(), format!("{}\n", version::agent_version()).into_bytes()));
+
+    match config_entries(&sources.config_path) {
+        Ok(entries) => files.extend(entries),
+        Err(e) => errors.push(format!("config {}: {}", sources.config_path.display(), e)),
+    }
+
+    match storage_tip(&sources.data_dir) {
+        Ok(tip) => files.push(("storage-tip.json".into(), serde_json::to_vec_pretty(&tip)?)),
+        Err(e) => errors.push(format!("audit files: {}", e)),
+    }
+
+    if let Ok(report) = fs::read(sources.data_dir.join(selfcheck::REPORT_FILE)) {
+        files.push((selfcheck::REPORT_FILE.into(), report));
+    }
+
+    match log_tails(&sources.data_dir.join("logs")) {
+        Ok(logs) => files.extend(logs),
+        Err(e) => errors.push(format!("logs: {}", e)),
+    }
+
+    if !errors.is_empty() {
+        files.push(("errors.txt".into(), errors.join("\n").into_bytes()));
+    }
+
+    let encoder = GzEncoder::new(fs::File::create(out)?, Compression::default());
+    let mut archive = tar::Builder::new(encoder);
+    for (name, data) in &files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        archive.append_data(&mut header, format!("support-bundle/{}", name), data.as_slice())?;
+    }
+    archive.into_inner()?.finish()?.flush()?;
+
+    Ok(files.into_iter().map(|(name, _)| name).collect())
+}
+
+/// Sanitized config plus the peer table it carries.
+///
+/// Stored proposals are left out of the config copy; `storage-tip.json`
+/// summarizes them instead.
+fn config_entries(path: &Path) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let config = Config::load_from_file(&path.to_string_lossy())?;
+    let peers = serde_json::to_value(&config.peer_manager)?;
+
+    let mut value = serde_json::to_value(&config)?;
+    if let Some(map) = value.as_object_mut() {
+        map.remove("storage");
+    }
+    redact(&mut value);
+
+    Ok(vec![
+        ("config.json".into(), serde_json::to_vec_pretty(&value)?),
+        ("peers.json".into(), serde_json::to_vec_pretty(&peers)?),
+    ])
+}
+
+/// Height, state root and counts of every audit file in `data_dir`.
+fn storage_tip(data_dir: &Path) -> io::Result<Value> {
+    let mut tips = Vec::new();
+
+    for path in sorted_entries(data_dir)? {
+        let name = file_name(&path);
+        if !(name.starts_with("audit-") && name.ends_with(".json")) {
+            continue;
+        }
+
+        let mut storage = Storage::new();
+        storage.apply_audit(load_audit(&path.to_string_lossy())?);
+        let root = storage.state_root();
+        tips.push(json!({
+            "file": name,
+            "height": root.height,
+            "state_root": hex::encode(root.root),
+            "proposals": storage.proposals.len(),
+            "results": storage.results.len(),
+            "certificates": storage.certificates.len(),
+            "last_proposal": storage.proposals.last().map(|p| p.id.clone()),
+        }));
+    }
+
+    Ok(Value::Array(tips))
+}
+
+/// Last [`LOG_TAIL_LINES`] lines of every file in `log_dir`.
+fn log_tails(log_dir: &Path) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut tails = Vec::new();
+
+    for path in sorted_entries(log_dir)? {
+        let text = fs::read_to_string(&path)?;
+        let lines: Vec<&str> = text.lines().collect();
+        let tail = lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].join("\n");
+        tails.push((format!("logs/{}", file_name(&path)), tail.into_bytes()));
+    }
+
+    Ok(tails)
+}
+
+fn sorted_entries(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_nested_secrets() {
+        let mut value = json!({
+            "node_id": "n1",
+            "tls": { "private_key": "abc", "cert": "pem" },
+            "peers": [{ "auth_token": "t", "id": "n2" }],
+        });
+        redact(&mut value);
+
+        assert_eq!(value["node_id"], "n1");
+        assert_eq!(value["tls"]["private_key"], REDACTED);
+        assert_eq!(value["tls"]["cert"], "pem");
+        assert_eq!(value["peers"][0]["auth_token"], REDACTED);
+        assert_eq!(value["peers"][0]["id"], "n2");
+    }
+
+    #[test]
+    fn test_bundle_records_missing_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("bundle.tar.gz");
+        let sources = BundleSources {
+            config_path: dir.path().join("missing.json"),
+            data_dir: dir.path().to_path_buf(),
+        };
+
+        let files = write_bundle(&sources, &out).unwrap();
+        assert!(files.contains(&"version.txt".to_string()));
+        assert!(files.contains(&"errors.txt".to_string()));
+        assert!(out.metadata().unwrap().len() > 0);
+    }
+}
@@ -0,0 +1,36 @@
+//! telemetry.rs
+//!
+//! OTLP export of tracing spans (`otlp` feature).
+//!
+//! The consensus pipeline — submission, proposal receipt, voting and
+//! commit — runs inside `tracing` spans that carry the proposal ID and,
+//! once committed, its height. With `--otlp-endpoint` those spans are also
+//! shipped to an OpenTelemetry collector. Trace context is not carried in
+//! gossip, so the same proposal is followed across nodes by its ID.
+
+use opentelemetry::{trace::TraceError, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use tracing::Subscriber;
+use tracing_subscriber::{registry::LookupSpan, Layer};
+
+/// Builds a layer exporting spans to the OTLP/gRPC collector at `endpoint`,
+/// tagged with `node` as the service instance.
+pub fn otlp_layer<S>(endpoint: &str, node: &str) -> Result<impl Layer<S>, TraceError>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let resource = Resource::new([
+        KeyValue::new("service.name", "atlas-node"),
+        KeyValue::new("service.instance.id", node.to_string()),
+        KeyValue::new("service.version", crate::version::VERSION),
+    ]);
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+        .with_trace_config(trace::config().with_resource(resource))
+        .install_batch(runtime::Tokio)?;
+
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
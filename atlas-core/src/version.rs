@@ -0,0 +1,159 @@
+//! version.rs
+//!
+//! Build identification and upgrade advisories.
+//!
+//! Every node advertises its crate version, git commit and protocol version
+//! in the libp2p identify agent string. Nodes track what their peers report
+//! and warn when a supermajority already runs a newer protocol, giving the
+//! operator advance notice of a required upgrade.
+//!
+//! Once a new protocol is agreed on, operators schedule it with an
+//! [`UpgradePlan`]: nodes still running an older protocol stop committing
+//! at the activation height instead of diverging from upgraded peers.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use atlas_sdk::utils::NodeId;
+
+/// Crate version of this build.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Git commit this binary was built from, or `unknown`.
+pub const GIT_COMMIT: &str = env!("ATLAS_GIT_COMMIT");
+
+/// Wire protocol version spoken by this build.
+///
+/// Bump whenever a change makes old and new nodes disagree on consensus.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+const AGENT_PREFIX: &str = "atlas-db/";
+
+/// Agent string advertised through identify,
+/// e.g. `atlas-db/0.2.0 commit=1a2b3c4d5e6f proto=1`.
+pub fn agent_version() -> String {
+    format!("{}{} commit={} proto={}", AGENT_PREFIX, VERSION, GIT_COMMIT, PROTOCOL_VERSION)
+}
+
+/// Version information parsed from a peer's agent string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerVersion {
+    pub version: String,
+    pub commit: String,
+    pub protocol: u32,
+}
+
+/// Parses an agent string produced by [`agent_version`].
+///
+/// Returns `None` for non-AtlasDB agents or malformed strings.
+pub fn parse_agent_version(agent: &str) -> Option<PeerVersion> {
+    let mut parts = agent.split_whitespace();
+    let version = parts.next()?.strip_prefix(AGENT_PREFIX)?.to_string();
+
+    let mut commit = None;
+    let mut protocol = None;
+    for part in parts {
+        match part.split_once('=') {
+            Some(("commit", v)) => commit = Some(v.to_string()),
+            Some(("proto", v)) => protocol = v.parse().ok(),
+            _ => {}
+        }
+    }
+
+    Some(PeerVersion { version, commit: commit?, protocol: protocol? })
+}
+
+/// Tracks the protocol versions reported by peers.
+#[derive(Debug, Default)]
+pub struct UpgradeAdvisory {
+    peers: HashMap<NodeId, u32>,
+    warned_for: Option<u32>,
+}
+
+impl UpgradeAdvisory {
+    /// Records the agent string of `peer`.
+    ///
+    /// Returns the newer protocol version the first time more than two
+    /// thirds of the known peers run a protocol newer than ours.
+    pub fn observe(&mut self, peer: NodeId, agent: &str) -> Option<u32> {
+        let protocol = parse_agent_version(agent)?.protocol;
+        self.peers.insert(peer, protocol);
+
+        let newer: Vec<u32> = self.peers.values().copied().filter(|p| *p > PROTOCOL_VERSION).collect();
+        if newer.len() * 3 <= self.peers.len() * 2 {
+            return None;
+        }
+
+        let target = newer.into_iter().min()?;
+        if self.warned_for.is_some_and(|w| w >= target) {
+            return None;
+        }
+        self.warned_for = Some(target);
+        Some(target)
+    }
+
+    /// Number of peers whose version is known.
+    pub fn known_peers(&self) -> usize {
+        self.peers.len()
+    }
+}
+
+/// A protocol upgrade scheduled at a committed height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradePlan {
+    /// Protocol version required from `activation_height` on.
+    pub protocol_version: u32,
+    /// First height (count of approved proposals) governed by the new protocol.
+    pub activation_height: u64,
+}
+
+impl UpgradePlan {
+    /// Whether this build may commit the proposal that brings the ledger to `height`.
+    pub fn allows_commit(&self, height: u64) -> bool {
+        PROTOCOL_VERSION >= self.protocol_version || height < self.activation_height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(proto: u32) -> String {
+        format!("atlas-db/9.9.9 commit=abc proto={}", proto)
+    }
+
+    #[test]
+    fn test_agent_version_roundtrip() {
+        let parsed = parse_agent_version(&agent_version()).unwrap();
+        assert_eq!(parsed.version, VERSION);
+        assert_eq!(parsed.commit, GIT_COMMIT);
+        assert_eq!(parsed.protocol, PROTOCOL_VERSION);
+
+        assert!(parse_agent_version("rust-libp2p/0.56").is_none());
+        assert!(parse_agent_version("atlas-db/1.0 commit=abc").is_none());
+    }
+
+    #[test]
+    fn test_advisory_requires_supermajority() {
+        let mut adv = UpgradeAdvisory::default();
+        assert_eq!(adv.observe(NodeId("a".into()), &agent(PROTOCOL_VERSION)), None);
+        assert_eq!(adv.observe(NodeId("b".into()), &agent(PROTOCOL_VERSION + 1)), None);
+        assert_eq!(adv.observe(NodeId("c".into()), &agent(PROTOCOL_VERSION + 1)), None);
+
+        // 3 of 4 peers are newer: warn once.
+        assert_eq!(adv.observe(NodeId("d".into()), &agent(PROTOCOL_VERSION + 1)), Some(PROTOCOL_VERSION + 1));
+        assert_eq!(adv.observe(NodeId("d".into()), &agent(PROTOCOL_VERSION + 1)), None);
+        assert_eq!(adv.known_peers(), 4);
+    }
+
+    #[test]
+    fn test_upgrade_plan_stops_old_builds_at_activation() {
+        let plan = UpgradePlan { protocol_version: PROTOCOL_VERSION + 1, activation_height: 10 };
+        assert!(plan.allows_commit(9));
+        assert!(!plan.allows_commit(10));
+
+        let current = UpgradePlan { protocol_version: PROTOCOL_VERSION, activation_height: 10 };
+        assert!(current.allows_commit(11));
+    }
+}
@@ -34,12 +34,12 @@ impl Authenticator for Ed25519Authenticator {
         }
     }
 
-    fn verify_with_key(&self, message: Vec<u8>, signature: &[u8; 64], public_key: &[u8]) -> Result<bool, String> {
+    fn verify_with_key(&self, message: &[u8], signature: &[u8; 64], public_key: &[u8]) -> Result<bool, String> {
         let verifying_key = VerifyingKey::from_bytes(public_key.try_into().map_err(|_| "Invalid public key length")?)
             .map_err(|e| e.to_string())?;
         let signature = Signature::from_slice(signature).map_err(|e| e.to_string())?;
         
-        match verifying_key.verify(&message, &signature) {
+        match verifying_key.verify(message, &signature) {
             Ok(_) => Ok(true),
             Err(_) => Ok(false),
         }
@@ -65,6 +65,7 @@ mod tests {
         let signature = auth.sign(message.to_vec()).expect("Signing failed");
 
         assert_eq!(signature.len(), 64);
+        let signature: [u8; 64] = signature.try_into().unwrap();
 
         let valid = auth.verify(message.to_vec(), &signature).expect("Verification failed");
         assert!(valid, "Signature should be valid");
@@ -0,0 +1,159 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    auth::Authenticator,
+    env::{
+        consensus::types::Vote,
+        vote_data::{vote_signing_bytes, VoteData},
+    },
+    utils::NodeId,
+};
+
+/// Proof that a proposal was approved by a quorum.
+///
+/// Bundles the individually signed `Yes` votes that made the proposal reach
+/// quorum, so light clients and auditors can check finality offline with
+/// nothing but the validators' public keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuorumCertificate {
+    pub proposal_id: String,
+    pub votes: Vec<VoteData>,
+}
+
+impl QuorumCertificate {
+    /// Builds a certificate from the `Yes` votes cast for `proposal_id`.
+    ///
+    /// Votes for other proposals or with other values are left out, and
+    /// votes are ordered by voter so the certificate is canonical.
+    pub fn new(proposal_id: &str, votes: impl IntoIterator<Item = VoteData>) -> Self {
+        let mut votes: Vec<VoteData> = votes
+            .into_iter()
+            .filter(|v| v.proposal_id == proposal_id && v.vote == Vote::Yes)
+            .collect();
+        votes.sort_by(|a, b| a.voter.cmp(&b.voter));
+        votes.dedup_by(|a, b| a.voter == b.voter);
+
+        Self { proposal_id: proposal_id.to_string(), votes }
+    }
+
+    /// Checks that the certificate holds at least `min_votes` distinct,
+    /// validly signed `Yes` votes for its proposal from trusted validators.
+    ///
+    /// `validators` maps each validator to its public key. Votes from
+    /// anyone else, or carrying a key other than the validator's, do not
+    /// count toward `min_votes`: the keys embedded in the certificate are
+    /// never trusted on their own.
+    pub fn verify(
+        &self,
+        auth: &dyn Authenticator,
+        validators: &HashMap<NodeId, Vec<u8>>,
+        min_votes: usize,
+    ) -> Result<(), String> {
+        let mut voters = HashSet::new();
+        let mut counted = 0;
+
+        for vote in &self.votes {
+            if vote.proposal_id != self.proposal_id {
+                return Err(format!("vote from {} is for another proposal", vote.voter));
+            }
+            if vote.vote != Vote::Yes {
+                return Err(format!("vote from {} is not Yes", vote.voter));
+            }
+            if !voters.insert(&vote.voter) {
+                return Err(format!("duplicate vote from {}", vote.voter));
+            }
+            let Some(key) = validators.get(&vote.voter).filter(|key| **key == vote.public_key) else {
+                continue;
+            };
+            if !auth.verify_with_key(&vote_signing_bytes(vote), &vote.signature, key)? {
+                return Err(format!("invalid signature from {}", vote.voter));
+            }
+            counted += 1;
+        }
+
+        if counted < min_votes {
+            return Err(format!("{} validator votes, quorum requires {}", counted, min_votes));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::ed25519::Ed25519Authenticator;
+    use ed25519_dalek::SigningKey;
+    use rand::rngs::OsRng;
+
+    fn signed_vote(proposal_id: &str, voter: &str, vote: Vote) -> VoteData {
+        let auth = Ed25519Authenticator::new(SigningKey::generate(&mut OsRng));
+        sign_as(&auth, proposal_id, voter, vote)
+    }
+
+    fn sign_as(auth: &Ed25519Authenticator, proposal_id: &str, voter: &str, vote: Vote) -> VoteData {
+        let mut data = VoteData {
+            proposal_id: proposal_id.to_string(),
+            vote,
+            voter: NodeId(voter.to_string()),
+            signature: [0u8; 64],
+            public_key: auth.public_key(),
+        };
+        data.signature = auth.sign(vote_signing_bytes(&data)).unwrap().try_into().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_certificate_verifies_quorum() {
+        let votes = vec![
+            signed_vote("p1", "n2", Vote::Yes),
+            signed_vote("p1", "n1", Vote::Yes),
+            signed_vote("p1", "n3", Vote::No),
+            signed_vote("p2", "n4", Vote::Yes),
+        ];
+        let validators = validators_of(&votes);
+        let qc = QuorumCertificate::new("p1", votes);
+        let auth = Ed25519Authenticator::new(SigningKey::generate(&mut OsRng));
+
+        assert_eq!(qc.votes.len(), 2);
+        assert_eq!(qc.votes[0].voter, NodeId("n1".into()));
+        assert!(qc.verify(&auth, &validators, 2).is_ok());
+        assert!(qc.verify(&auth, &validators, 3).is_err());
+    }
+
+    fn validators_of(votes: &[VoteData]) -> HashMap<NodeId, Vec<u8>> {
+        votes.iter().map(|v| (v.voter.clone(), v.public_key.clone())).collect()
+    }
+
+    #[test]
+    fn test_certificate_counts_only_trusted_validators() {
+        let honest = Ed25519Authenticator::new(SigningKey::generate(&mut OsRng));
+        let validators = validators_of(&[sign_as(&honest, "p1", "n1", Vote::Yes)]);
+        let auth = Ed25519Authenticator::new(SigningKey::generate(&mut OsRng));
+
+        // Fresh keys, built offline: one impersonates n1, the other is not a validator.
+        let forged = QuorumCertificate::new("p1", vec![
+            signed_vote("p1", "n1", Vote::Yes),
+            signed_vote("p1", "n2", Vote::Yes),
+        ]);
+        assert!(forged.verify(&auth, &validators, 1).is_err());
+
+        let genuine = QuorumCertificate::new("p1", vec![
+            sign_as(&honest, "p1", "n1", Vote::Yes),
+            signed_vote("p1", "n2", Vote::Yes),
+        ]);
+        assert!(genuine.verify(&auth, &validators, 1).is_ok());
+        assert!(genuine.verify(&auth, &validators, 2).is_err());
+    }
+
+    #[test]
+    fn test_certificate_rejects_tampered_vote() {
+        let mut qc = QuorumCertificate::new("p1", vec![signed_vote("p1", "n1", Vote::Yes)]);
+        qc.votes[0].voter = NodeId("n9".into());
+        let validators = validators_of(&qc.votes);
+
+        let auth = Ed25519Authenticator::new(SigningKey::generate(&mut OsRng));
+        assert!(qc.verify(&auth, &validators, 1).is_err());
+    }
+}
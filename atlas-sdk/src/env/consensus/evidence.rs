@@ -0,0 +1,83 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{
+    auth::Authenticator,
+    env::vote_data::{vote_signing_bytes, VoteData},
+    utils::NodeId,
+};
+
+/// Proof that a voter cast two different votes on the same proposal.
+///
+/// Both votes carry the voter's signature, so anyone holding the evidence
+/// can check the equivocation without trusting whoever reported it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteEquivocation {
+    pub first: VoteData,
+    pub second: VoteData,
+}
+
+impl VoteEquivocation {
+    /// Node that equivocated.
+    pub fn offender(&self) -> &NodeId {
+        &self.first.voter
+    }
+
+    /// Checks that both votes come from the same key, target the same
+    /// proposal, disagree, and are validly signed.
+    pub fn verify(&self, auth: &dyn Authenticator) -> Result<(), String> {
+        let (a, b) = (&self.first, &self.second);
+
+        if a.voter != b.voter || a.public_key != b.public_key {
+            return Err("votes come from different voters".to_string());
+        }
+        if a.proposal_id != b.proposal_id {
+            return Err("votes are for different proposals".to_string());
+        }
+        if a.vote == b.vote {
+            return Err("votes do not conflict".to_string());
+        }
+        for vote in [a, b] {
+            if !auth.verify_with_key(&vote_signing_bytes(vote), &vote.signature, &vote.public_key)? {
+                return Err(format!("invalid signature from {}", vote.voter));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{auth::ed25519::Ed25519Authenticator, env::consensus::types::Vote};
+    use ed25519_dalek::SigningKey;
+    use rand::rngs::OsRng;
+
+    fn signed_vote(auth: &Ed25519Authenticator, vote: Vote) -> VoteData {
+        let mut data = VoteData {
+            proposal_id: "p1".to_string(),
+            vote,
+            voter: NodeId("n1".to_string()),
+            signature: [0u8; 64],
+            public_key: auth.public_key(),
+        };
+        data.signature = auth.sign(vote_signing_bytes(&data)).unwrap().try_into().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_equivocation_requires_conflicting_signed_votes() {
+        let auth = Ed25519Authenticator::new(SigningKey::generate(&mut OsRng));
+        let evidence = VoteEquivocation {
+            first: signed_vote(&auth, Vote::Yes),
+            second: signed_vote(&auth, Vote::No),
+        };
+        assert!(evidence.verify(&auth).is_ok());
+
+        let same = VoteEquivocation { first: evidence.first.clone(), second: evidence.first.clone() };
+        assert!(same.verify(&auth).is_err());
+
+        let mut forged = evidence.clone();
+        forged.second.signature = [0u8; 64];
+        assert!(forged.verify(&auth).is_err());
+    }
+}
@@ -1 +1,3 @@
+pub mod certificate;
+pub mod evidence;
 pub mod types;
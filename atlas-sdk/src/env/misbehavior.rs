@@ -0,0 +1,40 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{env::consensus::evidence::VoteEquivocation, utils::NodeId};
+
+/// A node's signed report that a peer misbehaved, with the proof attached.
+///
+/// Reports are gossiped so that other nodes can lower their opinion of the
+/// offender without waiting to witness the misbehavior themselves. The
+/// evidence is checked on receipt; the reporter's signature only says who
+/// relayed it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MisbehaviorReport {
+    pub reporter: NodeId,
+    pub evidence: VoteEquivocation,
+    #[serde(with = "hex::serde")]
+    pub signature: [u8; 64],
+    pub public_key: Vec<u8>,
+}
+
+impl MisbehaviorReport {
+    pub fn bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("serialize misbehavior report")
+    }
+}
+
+#[derive(Serialize)]
+struct MisbehaviorSignView<'a> {
+    domain: &'a str,
+    reporter: &'a NodeId,
+    evidence: &'a VoteEquivocation,
+}
+
+/// Canonical bytes signed by the reporter of a [`MisbehaviorReport`].
+pub fn misbehavior_signing_bytes(r: &MisbehaviorReport) -> Vec<u8> {
+    bincode::serialize(&MisbehaviorSignView {
+        domain: super::signing_domains::MISBEHAVIOR,
+        reporter: &r.reporter,
+        evidence: &r.evidence,
+    }).expect("serialize sign view")
+}
@@ -1,6 +1,9 @@
 pub mod consensus;
+pub mod misbehavior;
 pub mod node;
 pub mod proposal;
+pub mod signing_domains;
+pub mod view_change;
 pub mod vote_data;
 
 use consensus::types::ConsensusResult;
@@ -21,6 +21,19 @@ pub struct Proposal {
     #[serde(with = "hex::serde")]
     pub signature: [u8; 64],
     pub public_key: Vec<u8>,
+
+    /// Client request ID used to trace the proposal across nodes.
+    ///
+    /// Metadata only: it is not part of the signed bytes.
+    ///
+    /// Wire compatibility: this field is appended to the bincode encoding
+    /// gossiped between nodes, and bincode has no notion of missing fields,
+    /// so `#[serde(default)]` only helps self-describing formats such as the
+    /// JSON audit file. Newer nodes still decode the old layout (see the
+    /// gossip envelope in atlas-core), but nodes built before this field
+    /// cannot decode proposals from newer ones.
+    #[serde(default)]
+    pub request_id: Option<String>,
 }
     
 impl Proposal {
@@ -52,4 +65,88 @@ pub fn signing_bytes(p: &Proposal) -> Vec<u8> {
         content: &p.content,
         parent: &p.parent,
     }).expect("serialize sign view")
-}
\ No newline at end of file
+}
+
+/// A signed proposal together with its canonical encodings.
+///
+/// The signing bytes and the bincode wire encoding are produced once, when
+/// the proposal is sealed, and then reused for signature checks and gossip
+/// instead of re-serializing the proposal at every step.
+#[derive(Debug, Clone)]
+pub struct SealedProposal {
+    proposal: Proposal,
+    signing: Vec<u8>,
+    wire: Vec<u8>,
+}
+
+impl SealedProposal {
+    /// Seals a signed proposal, encoding it for signing and for the wire.
+    pub fn new(proposal: Proposal) -> Self {
+        let signing = signing_bytes(&proposal);
+        Self::with_signing_bytes(proposal, signing)
+    }
+
+    /// Seals a proposal whose signing bytes were already computed to sign it.
+    ///
+    /// `signing` must be `signing_bytes(&proposal)`.
+    pub fn with_signing_bytes(proposal: Proposal, signing: Vec<u8>) -> Self {
+        debug_assert_eq!(signing, signing_bytes(&proposal));
+        let wire = bincode::serialize(&proposal).expect("serialize proposal");
+        Self { proposal, signing, wire }
+    }
+
+    /// Seals a proposal decoded from `wire`, keeping the received bytes as its
+    /// wire encoding.
+    pub fn from_wire(proposal: Proposal, wire: Vec<u8>) -> Self {
+        let signing = signing_bytes(&proposal);
+        Self { proposal, signing, wire }
+    }
+
+    pub fn proposal(&self) -> &Proposal {
+        &self.proposal
+    }
+
+    /// Bytes covered by the proposal signature.
+    pub fn signing_bytes(&self) -> &[u8] {
+        &self.signing
+    }
+
+    /// Bincode encoding published over gossip.
+    pub fn wire_bytes(&self) -> &[u8] {
+        &self.wire
+    }
+
+    pub fn into_proposal(self) -> Proposal {
+        self.proposal
+    }
+
+    /// Splits into the proposal and its wire encoding.
+    pub fn into_parts(self) -> (Proposal, Vec<u8>) {
+        (self.proposal, self.wire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sealed_proposal_matches_fresh_encodings() {
+        let proposal = Proposal {
+            id: "p1".into(),
+            proposer: NodeId("n1".into()),
+            content: "{}".into(),
+            parent: None,
+            signature: [7u8; 64],
+            public_key: vec![1, 2, 3],
+            request_id: Some("req".into()),
+        };
+
+        let sealed = SealedProposal::new(proposal.clone());
+        assert_eq!(sealed.signing_bytes(), signing_bytes(&proposal).as_slice());
+        assert_eq!(sealed.wire_bytes(), proposal.bytes().as_slice());
+
+        let received = SealedProposal::from_wire(proposal.clone(), proposal.bytes());
+        assert_eq!(received.signing_bytes(), sealed.signing_bytes());
+    }
+}
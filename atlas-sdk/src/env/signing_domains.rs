@@ -0,0 +1,32 @@
+//! signing_domains.rs
+//!
+//! Domain-separation prefixes for the messages a node signs.
+//!
+//! Newer sign views start with one of these strings, so a signature made
+//! for one kind of message can never be replayed as another. Proposals and
+//! votes predate domain separation and are signed without a prefix; adding
+//! one would change their signing bytes and split mixed-version networks.
+//! Their sign views are distinct bincode layouts, and neither starts with
+//! a string that could be mistaken for a domain below.
+//!
+//! External implementations can check their encoders against the JSON
+//! vectors in `atlas-conformance/vectors/signing.json`.
+
+/// Domain of [`ViewChange`](super::view_change::ViewChange) signatures.
+pub const VIEW_CHANGE: &str = "atlas/view-change";
+
+/// Domain of [`MisbehaviorReport`](super::misbehavior::MisbehaviorReport)
+/// signatures, which also cover the attached evidence.
+pub const MISBEHAVIOR: &str = "atlas/misbehavior";
+
+/// Domain of admin request signatures, see [`crate::auth::admin`].
+pub const ADMIN: &str = "atlas/admin";
+
+/// Every signed message kind and its domain, `None` for unprefixed legacy kinds.
+pub const ALL: &[(&str, Option<&str>)] = &[
+    ("proposal", None),
+    ("vote", None),
+    ("view_change", Some(VIEW_CHANGE)),
+    ("misbehavior", Some(MISBEHAVIOR)),
+    ("admin", Some(ADMIN)),
+];
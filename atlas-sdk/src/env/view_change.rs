@@ -0,0 +1,39 @@
+use serde::{Serialize, Deserialize};
+
+use crate::utils::NodeId;
+
+/// A node's signed request to move the cluster to `new_view`.
+///
+/// Nodes send it when the current leader stops responding. Once a quorum
+/// of nodes asks for the same view, every node moves to it and re-runs the
+/// election with the new view as part of the seed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewChange {
+    pub new_view: u64,
+    pub voter: NodeId,
+    #[serde(with = "hex::serde")]
+    pub signature: [u8; 64],
+    pub public_key: Vec<u8>,
+}
+
+impl ViewChange {
+    pub fn bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("serialize view change")
+    }
+}
+
+#[derive(Serialize)]
+struct ViewChangeSignView<'a> {
+    domain: &'a str,
+    new_view: u64,
+    voter: &'a NodeId,
+}
+
+/// Canonical bytes signed by the voter of a [`ViewChange`].
+pub fn view_change_signing_bytes(v: &ViewChange) -> Vec<u8> {
+    bincode::serialize(&ViewChangeSignView {
+        domain: super::signing_domains::VIEW_CHANGE,
+        new_view: v.new_view,
+        voter: &v.voter,
+    }).expect("serialize sign view")
+}